@@ -1,25 +1,94 @@
 use crate::literal::Literal;
 use crate::token::Token;
 use std::fmt;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-#[derive(Debug, Clone)]
+// Identifies a variable reference, so the resolver can record where it points without
+// hashing the whole expression
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    // Unique across parsers, as the interpreter keeps the resolutions of every script,
+    // REPL line and module it runs
+    pub fn next() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        NodeId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+// How a method declared in a class body is invoked
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum MethodKind {
+    Method,
+    // `class name() {}`, called on the class itself
+    Static,
+    // `name {}`, called without parentheses when the property is read
+    Getter,
+    // `name=(value) {}`, called when the property is assigned
+    Setter,
+}
+
+// Optional type annotations of a function's parameters and return value. They are
+// only read by the type checker and ignored at runtime.
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq)]
+pub struct Signature {
+    pub params: Vec<Option<Token>>,
+    pub return_type: Option<Token>,
+}
+
+// A single pattern of a match case
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub enum Pattern {
+    // Matches values equal to the literal, written starting at the token
+    Literal(Token, Literal),
+    // Matches instances of the named class or one of its subclasses
+    Class(Expr),
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub enum Stmt {
     Block(Vec<Stmt>),
     Expr(Expr),
-    Function(Token, Vec<Token>, Vec<Stmt>),
+    Function(Token, Vec<Token>, Rc<[Stmt]>, Signature),
     If(Expr, Box<Stmt>, Option<Box<Stmt>>),
     Print(Expr),
     Return(Token, Option<Expr>),
-    Var(Token, Option<Expr>),
-    While(Expr, Box<Stmt>),
-    Class(Token, Option<Expr>, Vec<Stmt>),
+    // Name, optional type annotation and initializer
+    Var(Token, Option<Token>, Option<Expr>),
+    // Condition, body and the increment of a desugared for loop
+    While(Expr, Box<Stmt>, Option<Expr>),
+    // Loop variable, iterated expression and body of a for-in loop
+    ForIn(Token, Expr, Box<Stmt>),
+    Class(Token, Option<Expr>, Vec<(MethodKind, Stmt)>),
+    Break(Token),
+    Continue(Token),
+    Throw(Token, Expr),
+    // `import` keyword, module path and the name the module is bound to
+    Import(Token, String, Token),
+    // A var, fun or class declaration visible to importing modules
+    Export(Box<Stmt>),
+    // Body, optional catch variable and handler, and optional finally block
+    Try(Vec<Stmt>, Option<(Token, Vec<Stmt>)>, Option<Vec<Stmt>>),
+    // `match` keyword, subject, cases with their patterns and the default case
+    Match(Token, Expr, Vec<(Vec<Pattern>, Stmt)>, Option<Box<Stmt>>),
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Pattern::Literal(_, ref literal) => write!(f, "{}", literal),
+            Pattern::Class(ref class) => write!(f, "{}", class),
+        }
+    }
 }
 
 impl fmt::Display for Stmt {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             Stmt::Expr(ref e) | Stmt::Print(ref e) => write!(f, "{}", e),
-            Stmt::Var(ref name, ref init) => match init {
+            Stmt::Var(ref name, _, ref init) => match init {
                 Some(ref init) => write!(f, "({} {})", name, init),
                 None => write!(f, "({})", name),
             },
@@ -28,7 +97,7 @@ impl fmt::Display for Stmt {
                 for s in stmts {
                     output.push_str(format!("({})", s).as_str());
                 }
-                return write!(f, "({})", output);
+                write!(f, "({})", output)
             }
             Stmt::If(ref condition, ref consequent, ref alternative) => {
                 if let Some(alt) = alternative {
@@ -37,8 +106,12 @@ impl fmt::Display for Stmt {
                     write!(f, "({} {})", condition, consequent)
                 }
             }
-            Stmt::While(ref condition, ref body) => {
-                write!(f, "({} {})", condition, body)
+            Stmt::While(ref condition, ref body, ref increment) => match increment {
+                Some(increment) => write!(f, "({} {} {})", condition, body, increment),
+                None => write!(f, "({} {})", condition, body),
+            },
+            Stmt::ForIn(ref name, ref iterable, ref body) => {
+                write!(f, "(for {} in {} {})", name, iterable, body)
             }
             Stmt::Function(ref name, ..) => write!(f, "function {}", name),
             Stmt::Return(ref _return, ref return_value) => match return_value {
@@ -49,24 +122,99 @@ impl fmt::Display for Stmt {
                 Some(n) => write!(f, "class {}", n),
                 None => write!(f, "class"),
             },
+            Stmt::Break(_) => write!(f, "break"),
+            Stmt::Continue(_) => write!(f, "continue"),
+            Stmt::Throw(_, ref value) => write!(f, "throw {}", value),
+            Stmt::Import(_, ref path, ref name) => write!(f, "import {} as {}", path, name),
+            Stmt::Export(ref declaration) => write!(f, "export {}", declaration),
+            Stmt::Try(ref body, ref catch, ref finally) => {
+                write!(f, "(try {:?}", body)?;
+                if let Some((name, handler)) = catch {
+                    write!(f, " catch {} {:?}", name, handler)?;
+                }
+                if let Some(finally) = finally {
+                    write!(f, " finally {:?}", finally)?;
+                }
+                write!(f, ")")
+            }
+            Stmt::Match(_, ref subject, ref cases, ref default) => {
+                write!(f, "(match {}", subject)?;
+                for (patterns, body) in cases {
+                    let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+                    write!(f, " (case {} {})", patterns.join(", "), body)?;
+                }
+                if let Some(default) = default {
+                    write!(f, " (default {})", default)?;
+                }
+                write!(f, ")")
+            }
         }
     }
 }
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub enum Expr {
-    Assign(Token, Box<Expr>),
+    Assign(Token, Box<Expr>, NodeId),
     Binary(Box<Expr>, Token, Box<Expr>),
     Call(Box<Expr>, Token, Vec<Expr>),
+    // Evaluates both sides and yields the right one
+    Comma(Box<Expr>, Token, Box<Expr>),
+    // Target variable, property or subscript, operator such as `+=`, and value
+    CompoundAssign(Box<Expr>, Token, Box<Expr>),
+    // Condition, `?` token, then and else branches
+    Conditional(Box<Expr>, Token, Box<Expr>, Box<Expr>),
     Get(Box<Expr>, Token),
     Grouping(Box<Expr>),
+    // Target, `++` or `--`, and whether the operator is a prefix
+    Increment(Box<Expr>, Token, bool),
+    // Anonymous function with its `fun` keyword, parameters and body
+    Lambda(Token, Vec<Token>, Rc<[Stmt]>, Signature),
+    List(Vec<Expr>),
     Literal(Literal),
     Logical(Box<Expr>, Token, Box<Expr>),
+    // Key and value expressions of a map literal
+    Map(Vec<(Expr, Expr)>),
     Set(Box<Expr>, Token, Box<Expr>),
-    Super(Token, Token),
-    This(Token),
+    // Printed form of an interpolated expression
+    Stringify(Box<Expr>),
+    // Object, closing bracket, index and new value
+    SetSubscript(Box<Expr>, Token, Box<Expr>, Box<Expr>),
+    // Object, closing bracket and index
+    Subscript(Box<Expr>, Token, Box<Expr>),
+    Super(Token, Token, NodeId),
+    This(Token, NodeId),
     Unary(Token, Box<Expr>),
-    Variable(Token),
+    Variable(Token, NodeId),
+}
+
+impl Expr {
+    // Source line of the token this expression carries, if it has one
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Expr::Assign(token, ..)
+            | Expr::Binary(_, token, _)
+            | Expr::Call(_, token, _)
+            | Expr::Comma(_, token, _)
+            | Expr::CompoundAssign(_, token, _)
+            | Expr::Increment(_, token, _)
+            | Expr::Conditional(_, token, ..)
+            | Expr::Get(_, token)
+            | Expr::Lambda(token, ..)
+            | Expr::Logical(_, token, _)
+            | Expr::Set(_, token, _)
+            | Expr::SetSubscript(_, token, ..)
+            | Expr::Subscript(_, token, _)
+            | Expr::Super(token, ..)
+            | Expr::This(token, _)
+            | Expr::Unary(token, _)
+            | Expr::Variable(token, _) => Some(token.line),
+            Expr::Grouping(_)
+            | Expr::List(_)
+            | Expr::Literal(_)
+            | Expr::Map(_)
+            | Expr::Stringify(_) => None,
+        }
+    }
 }
 
 impl fmt::Display for Expr {
@@ -81,6 +229,9 @@ impl fmt::Display for Expr {
             Expr::Literal(ref literal) => {
                 write!(f, "{}", literal)
             }
+            Expr::Stringify(ref expr) => {
+                write!(f, "(str {})", expr)
+            }
             Expr::Logical(ref left, ref operator, ref right) => {
                 write!(f, "({} {} {})", left, operator, right)
             }
@@ -92,27 +243,58 @@ impl fmt::Display for Expr {
                     expr
                 )
             }
-            Expr::Variable(ref token) => {
+            Expr::Variable(ref token, _) => {
                 write!(f, "{}", token)
             }
-            Expr::Assign(ref name, ref init) => {
+            Expr::Assign(ref name, ref init, _) => {
                 write!(f, "({} = {})", name, init)
             }
             Expr::Call(ref callee, ref _paren, ref args) => {
                 write!(f, "(functionCall {} ({:?}))", callee, args)
             }
+            Expr::Comma(ref left, _, ref right) => {
+                write!(f, "(, {} {})", left, right)
+            }
+            Expr::CompoundAssign(ref target, ref operator, ref value) => {
+                write!(f, "({:?} {} {})", operator.token_type, target, value)
+            }
+            Expr::Increment(ref target, ref operator, prefix) => {
+                if prefix {
+                    write!(f, "(pre{:?} {})", operator.token_type, target)
+                } else {
+                    write!(f, "(post{:?} {})", operator.token_type, target)
+                }
+            }
+            Expr::Conditional(ref condition, _, ref then_branch, ref else_branch) => {
+                write!(f, "(? {} {} {})", condition, then_branch, else_branch)
+            }
             Expr::Get(ref object, ref name) => {
                 write!(f, "(Instance access {} ({:?}))", object, name)
             }
             Expr::Set(ref object, ref name, ref new_value) => {
                 write!(f, "({}.{} = {}", object, name, new_value)
             }
-            Expr::Super(ref keyword, ref method) => {
+            Expr::Super(ref keyword, ref method, _) => {
                 write!(f, "{} {}", keyword, method)
             }
-            Expr::This(ref name) => {
+            Expr::This(ref name, _) => {
                 write!(f, "{}", name)
             }
+            Expr::List(ref elements) => {
+                write!(f, "(list {:?})", elements)
+            }
+            Expr::Lambda(_, ref params, ..) => {
+                write!(f, "(lambda/{})", params.len())
+            }
+            Expr::Map(ref entries) => {
+                write!(f, "(map {:?})", entries)
+            }
+            Expr::Subscript(ref object, _, ref index) => {
+                write!(f, "({}[{}])", object, index)
+            }
+            Expr::SetSubscript(ref object, _, ref index, ref new_value) => {
+                write!(f, "({}[{}] = {})", object, index, new_value)
+            }
         }
     }
 }
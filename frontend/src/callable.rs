@@ -1,14 +1,17 @@
 use crate::instance::Instance;
 use crate::literal::Literal;
-use crate::runnable::Runnable;
-use crate::scanner::ScannerResult;
+use crate::runnable::{InterpreterResult, Runnable};
 use std::cmp::{Ordering, PartialEq, PartialOrd};
 use std::fmt::Debug;
 
 pub trait Callable: Debug {
     fn arity(&self) -> usize;
 
-    fn call(&self, interpreter: &mut dyn Runnable, args: Vec<Literal>) -> ScannerResult<Literal>;
+    fn call(
+        &self,
+        interpreter: &mut dyn Runnable,
+        args: Vec<Literal>,
+    ) -> InterpreterResult<Literal>;
 
     fn box_clone(&self) -> Box<dyn Callable>;
 
@@ -1,37 +1,92 @@
 use crate::callable::Callable;
 use crate::instance::Instance;
 use crate::literal::Literal;
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt::Display;
+use std::rc::Rc;
 
-#[derive(Debug, Clone)]
+// Methods of a class, grouped by how they are invoked
+#[derive(Debug, Clone, Default)]
+pub struct Methods {
+    pub instance: HashMap<String, Literal>,
+    pub getters: HashMap<String, Literal>,
+    pub setters: HashMap<String, Literal>,
+    // Called on the class itself rather than on an instance
+    pub statics: HashMap<String, Literal>,
+}
+
+// Classes are shared by reference between their instances, bound methods and subclasses
+#[derive(Debug)]
 pub struct Class {
     name: String,
-    methods: HashMap<String, Literal>,
-    super_class: Option<Box<Class>>,
+    // Boxed to keep Literal small
+    methods: Box<Methods>,
+    // Static fields assigned on the class, visible through every instance
+    fields: RefCell<HashMap<String, Literal>>,
+    super_class: Option<Rc<Class>>,
 }
 
 impl Class {
-    pub fn new(
-        name: String,
-        super_class: Option<Box<Class>>,
-        methods: HashMap<String, Literal>,
-    ) -> Self {
+    pub fn new(name: String, super_class: Option<Rc<Class>>, methods: Methods) -> Self {
         Class {
             name,
-            methods,
+            methods: Box::new(methods),
+            fields: RefCell::new(HashMap::new()),
             super_class,
         }
     }
 
     pub fn get_method(&self, name: &str) -> Option<Literal> {
+        self.find(name, |m| &m.instance)
+    }
+
+    pub fn get_getter(&self, name: &str) -> Option<Literal> {
+        self.find(name, |m| &m.getters)
+    }
+
+    pub fn get_setter(&self, name: &str) -> Option<Literal> {
+        self.find(name, |m| &m.setters)
+    }
+
+    pub fn get_static(&self, name: &str) -> Option<Literal> {
+        self.find(name, |m| &m.statics)
+    }
+
+    // Looks up a static field, falling back to its super classes
+    pub fn get_field(&self, name: &str) -> Option<Literal> {
+        if let Some(field) = self.fields.borrow().get(name) {
+            return Some(field.clone());
+        }
+        self.super_class.as_ref()?.get_field(name)
+    }
+
+    pub fn set_field(&self, name: String, value: Literal) {
+        self.fields.borrow_mut().insert(name, value);
+    }
+
+    // Whether this class is `other` or one of its subclasses
+    pub fn is_subclass_of(&self, other: &Class) -> bool {
+        std::ptr::eq(self, other)
+            || self
+                .super_class
+                .as_ref()
+                .is_some_and(|super_class| super_class.is_subclass_of(other))
+    }
+
+    // Looks up a method in this class, falling back to its super classes
+    fn find(
+        &self,
+        name: &str,
+        table: fn(&Methods) -> &HashMap<String, Literal>,
+    ) -> Option<Literal> {
         // TODO HACK to_owned might clone
-        let own_method = self.methods.get(name).map(|m| m.to_owned());
+        let own_method = table(&self.methods).get(name).map(|m| m.to_owned());
         if own_method.is_some() {
             return own_method;
         }
         if let Some(super_class) = self.super_class.as_ref() {
-            return super_class.get_method(name);
+            return super_class.find(name, table);
         }
         None
     }
@@ -43,7 +98,8 @@ impl Display for Class {
     }
 }
 
-impl Callable for Class {
+// Implemented on the Rc so instances can share the class they were created from
+impl Callable for Rc<Class> {
     fn arity(&self) -> usize {
         let init = self.get_method("init");
         if let Some(Literal::Callable(init)) = init {
@@ -53,16 +109,15 @@ impl Callable for Class {
     }
 
     fn box_clone(&self) -> Box<dyn Callable> {
-        Box::new(self.clone())
+        Box::new(Rc::clone(self))
     }
 
     fn call(
         &self,
         interpreter: &mut dyn crate::runnable::Runnable,
         args: Vec<crate::literal::Literal>,
-    ) -> crate::scanner::ScannerResult<crate::literal::Literal> {
-        // HACK: cloning class to create instance for now to avoid messy lifetimes
-        let instance = Instance::new(self.clone());
+    ) -> crate::runnable::InterpreterResult<crate::literal::Literal> {
+        let instance = Instance::new(Rc::clone(self));
         let init = self.get_method("init");
         if let Some(Literal::Callable(init)) = init {
             init.bind(instance.clone()).call(interpreter, args)?;
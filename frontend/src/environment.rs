@@ -1,4 +1,5 @@
 use crate::literal::Literal;
+use crate::token::Token;
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 use utils::errors::InterpreterError;
 
@@ -6,7 +7,12 @@ type InterpreterResult<T> = Result<T, InterpreterError>;
 
 #[derive(Debug, Clone)]
 pub struct Environment {
+    // Variables of a top-level scope, which the resolver leaves to be looked up by name
     pub values: HashMap<String, Literal>,
+    // Variables of a local scope, indexed by the slot the resolver assigned them. Names
+    // are kept to check resolved accesses.
+    slots: Vec<(String, Literal)>,
+    top_level: bool,
     pub enclosing: Option<Rc<RefCell<Environment>>>,
 }
 
@@ -14,12 +20,29 @@ impl Environment {
     pub fn new(enclosing: Option<Rc<RefCell<Environment>>>) -> Self {
         Environment {
             values: HashMap::new(),
+            slots: Vec::new(),
+            // The globals have no enclosing scope
+            top_level: enclosing.is_none(),
             enclosing,
         }
     }
 
+    // Scope of a module, which like the globals is looked up by name
+    pub fn top_level(enclosing: Rc<RefCell<Environment>>) -> Self {
+        Environment {
+            top_level: true,
+            ..Environment::new(Some(enclosing))
+        }
+    }
+
+    // Locals take the next slot, so they must be defined in the order the resolver
+    // declared them
     pub fn define(&mut self, name: String, value: Literal) {
-        self.values.insert(name, value);
+        if self.top_level {
+            self.values.insert(name, value);
+        } else {
+            self.slots.push((name, value));
+        }
     }
 
     pub fn get(&self, name: &str) -> Option<Literal> {
@@ -34,22 +57,42 @@ impl Environment {
         None
     }
 
-    pub fn get_at(&mut self, distance: usize, name: &str) -> Option<Literal> {
+    // Value of the innermost local with that name, looked up without resolving it
+    pub fn get_local(&self, name: &str) -> Option<Literal> {
+        if let Some((_, value)) = self.slots.iter().rev().find(|(n, _)| n == name) {
+            return Some(value.clone());
+        }
+        self.enclosing.as_ref()?.borrow().get_local(name)
+    }
+
+    // Reads a slot the interpreter bound itself, such as `this` of a bound method
+    pub fn get_slot(&self, slot: usize) -> Option<Literal> {
+        self.slots.get(slot).map(|(_, value)| value.clone())
+    }
+
+    pub fn get_at(&self, distance: usize, slot: usize, name: &Token) -> InterpreterResult<Literal> {
         if distance == 0 {
-            return self.values.get(name).cloned();
+            return self.slot(slot, name, distance).cloned();
         }
-        self.ancestor(distance).borrow().get(name)
+        let ancestor = self.ancestor(distance, name)?;
+        let value = ancestor.borrow().slot(slot, name, distance)?.clone();
+        Ok(value)
     }
 
-    fn ancestor(&self, distance: usize) -> Rc<RefCell<Environment>> {
-        let mut curr_env = Rc::clone(self.enclosing.as_ref().unwrap());
-        for _i in 1..distance {
-            let curr_env_ref = Rc::clone(&curr_env);
-            if let Some(encl) = &curr_env_ref.borrow().enclosing {
-                curr_env = Rc::clone(encl);
-            };
+    pub fn assign_at(
+        &mut self,
+        distance: usize,
+        slot: usize,
+        name: &Token,
+        value: Literal,
+    ) -> InterpreterResult<()> {
+        if distance == 0 {
+            *self.slot_mut(slot, name, distance)? = value;
+            return Ok(());
         }
-        Rc::clone(&curr_env)
+        let ancestor = self.ancestor(distance, name)?;
+        *ancestor.borrow_mut().slot_mut(slot, name, distance)? = value;
+        Ok(())
     }
 
     pub fn assign(&mut self, name: String, value: Literal) -> InterpreterResult<()> {
@@ -66,20 +109,54 @@ impl Environment {
         Err(InterpreterError::UndefinedVariable(name))
     }
 
-    pub fn assign_at(
+    // Distance of the innermost local scope defining the name, found by walking the
+    // scope chain as if nothing had been resolved
+    pub fn local_distance(&self, name: &str) -> Option<usize> {
+        if self.slots.iter().any(|(n, _)| n == name) {
+            return Some(0);
+        }
+        let parent = self.enclosing.as_ref()?;
+        parent.borrow().local_distance(name).map(|d| d + 1)
+    }
+
+    fn ancestor(
+        &self,
+        distance: usize,
+        name: &Token,
+    ) -> InterpreterResult<Rc<RefCell<Environment>>> {
+        let missing = || unresolved(name, distance);
+        let mut curr_env = Rc::clone(self.enclosing.as_ref().ok_or_else(missing)?);
+        for _i in 1..distance {
+            let encl = Rc::clone(curr_env.borrow().enclosing.as_ref().ok_or_else(missing)?);
+            curr_env = encl;
+        }
+        Ok(curr_env)
+    }
+
+    fn slot(&self, slot: usize, name: &Token, distance: usize) -> InterpreterResult<&Literal> {
+        match self.slots.get(slot) {
+            Some((n, value)) if Some(n) == name.lexeme.as_ref() => Ok(value),
+            _ => Err(unresolved(name, distance)),
+        }
+    }
+
+    fn slot_mut(
         &mut self,
+        slot: usize,
+        name: &Token,
         distance: usize,
-        name: String,
-        value: Literal,
-    ) -> InterpreterResult<()> {
-        self.ancestor(distance)
-            .borrow_mut()
-            .values
-            .insert(name, value);
-        Ok(())
+    ) -> InterpreterResult<&mut Literal> {
+        match self.slots.get_mut(slot) {
+            Some((n, value)) if Some(&*n) == name.lexeme.as_ref() => Ok(value),
+            _ => Err(unresolved(name, distance)),
+        }
     }
 
     pub fn into_cell(self) -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(self))
     }
 }
+
+fn unresolved(name: &Token, distance: usize) -> InterpreterError {
+    InterpreterError::UnresolvedLocal(name.lexeme.clone().unwrap_or_default(), distance, name.line)
+}
@@ -1,8 +1,7 @@
-use crate::scanner::ScannerResult;
+use crate::runnable::InterpreterResult;
 use crate::{instance::Instance, runnable::EarlyReturn};
 use std::cell::RefCell;
 use std::rc::Rc;
-use utils::errors::ScannerError;
 
 use crate::{
     ast::Stmt, callable::Callable, environment::Environment, literal::Literal, token::Token,
@@ -10,10 +9,13 @@ use crate::{
 
 #[derive(Debug, Clone)]
 pub struct Function {
-    params: Vec<Token>,
-    body: Vec<Stmt>,
+    // Shared with the AST, so creating and binding functions does not copy the body
+    params: Rc<[Token]>,
+    body: Rc<[Stmt]>,
     closure: Rc<RefCell<Environment>>,
     is_init: bool,
+    // Whether the innermost scope of the closure holds `this`, which binding replaces
+    bound: bool,
 }
 
 impl Callable for Function {
@@ -25,7 +27,7 @@ impl Callable for Function {
         &self,
         interpreter: &mut dyn crate::runnable::Runnable,
         args: Vec<crate::literal::Literal>,
-    ) -> ScannerResult<crate::literal::Literal> {
+    ) -> InterpreterResult<crate::literal::Literal> {
         let mut curr_env = Environment::new(Some(Rc::clone(&self.closure)));
 
         for (n, p) in args.into_iter().enumerate() {
@@ -34,24 +36,26 @@ impl Callable for Function {
             }
         }
 
-        let res = interpreter.block(self.body.clone(), curr_env.into_cell());
+        let res = interpreter.block(&self.body, curr_env.into_cell());
 
         match res {
             Ok(_) => {
                 if self.is_init {
-                    let this_method = self.closure.borrow_mut().get_at(0, "init");
-                    if let Some(this_method) = this_method {
-                        return Ok(this_method);
+                    if let Some(this) = self.this() {
+                        return Ok(this);
                     }
                 }
                 Ok(Literal::Nil)
             }
             Err(e) => match e {
-                EarlyReturn::Error(_) => Err(ScannerError::UnknownError),
+                // The resolver rejects break and continue outside of a loop body
+                EarlyReturn::Error(_)
+                | EarlyReturn::Throw(_)
+                | EarlyReturn::Break
+                | EarlyReturn::Continue => Err(e),
                 EarlyReturn::Return(val) => {
                     if self.is_init {
-                        let this = self.closure.borrow_mut().get_at(0, "this");
-                        if let Some(this) = this {
+                        if let Some(this) = self.this() {
                             return Ok(this);
                         }
                     }
@@ -66,21 +70,25 @@ impl Callable for Function {
     }
 
     fn bind(&self, instance: Instance) -> Box<dyn Callable> {
-        let mut env = Environment::new(Some(Rc::clone(&self.closure)));
+        let enclosing = if self.bound {
+            self.closure.borrow().enclosing.clone()
+        } else {
+            Some(Rc::clone(&self.closure))
+        };
+        let mut env = Environment::new(enclosing);
         env.define("this".to_string(), Literal::Instance(instance));
-        Box::new(Function::new(
-            self.params.clone(),
-            self.body.clone(),
-            Rc::new(RefCell::new(env)),
-            self.is_init,
-        ))
+        Box::new(Function {
+            closure: env.into_cell(),
+            bound: true,
+            ..self.clone()
+        })
     }
 }
 
 impl Function {
     pub fn new(
-        params: Vec<Token>,
-        body: Vec<Stmt>,
+        params: Rc<[Token]>,
+        body: Rc<[Stmt]>,
         closure: Rc<RefCell<Environment>>,
         is_init: bool,
     ) -> Self {
@@ -89,6 +97,29 @@ impl Function {
             body,
             closure,
             is_init,
+            bound: false,
         }
     }
+
+    // Lambdas close over their own `this`, so one stored on a class is bound to the
+    // instance it is read from like a method. Until then `this` is the one in scope where
+    // the lambda was created, or nil.
+    pub fn lambda(
+        params: Rc<[Token]>,
+        body: Rc<[Stmt]>,
+        closure: Rc<RefCell<Environment>>,
+        this: Literal,
+    ) -> Self {
+        let mut env = Environment::new(Some(closure));
+        env.define("this".to_string(), this);
+        Function {
+            bound: true,
+            ..Function::new(params, body, env.into_cell(), false)
+        }
+    }
+
+    // Bound methods close over a scope holding only `this`
+    fn this(&self) -> Option<Literal> {
+        self.closure.borrow().get_slot(0)
+    }
 }
@@ -1,53 +1,104 @@
+use crate::callable::Callable;
 use crate::class::Class;
 use crate::literal::Literal;
+use crate::runnable::{InterpreterResult, Runnable};
 use crate::token::Token;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt::Display;
+use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
 #[derive(Debug)]
 pub struct Instance {
-    class: Class,
+    class: Rc<Class>,
     pub fields: Rc<RefCell<HashMap<String, Literal>>>,
 }
 
 impl Instance {
-    pub fn new(class: Class) -> Self {
+    pub fn new(class: Rc<Class>) -> Self {
         let fields = Rc::new(RefCell::new(HashMap::new()));
         Instance { class, fields }
     }
 
-    pub fn get(&self, name: Token) -> Literal {
+    pub fn get(&self, name: Token, interpreter: &mut dyn Runnable) -> InterpreterResult<Literal> {
         if let Some(name) = name.lexeme {
             if let Some(field) = self.fields.borrow().get(name.as_str()) {
                 // TODO HACK check if cloning leads to issues
-                return field.clone();
+                return Ok(field.clone());
+            }
+            // Getters run as soon as the property is read
+            if let Some(Literal::Callable(getter)) = self.class.get_getter(name.as_str()) {
+                return getter.bind(self.clone()).call(interpreter, Vec::new());
             }
             if let Some(Literal::Callable(method)) = self.class.get_method(name.as_str()) {
-                return Literal::Callable(method.bind(self.clone()));
+                return Ok(Literal::Callable(method.bind(self.clone())));
+            }
+            // Static fields are shared by all instances of the class, and a function stored
+            // on the class is bound like a method
+            match self.class.get_field(name.as_str()) {
+                Some(Literal::Callable(method)) => {
+                    return Ok(Literal::Callable(method.bind(self.clone())))
+                }
+                Some(field) => return Ok(field),
+                None => {}
             }
         };
-        Literal::Nil
+        Ok(Literal::Nil)
+    }
+
+    // Looks up a class method bound to this instance, ignoring fields
+    pub fn get_method(&self, name: &str) -> Option<Box<dyn Callable>> {
+        if let Some(Literal::Callable(method)) = self.class.get_method(name) {
+            return Some(method.bind(self.clone()));
+        }
+        None
+    }
+
+    pub fn is_instance_of(&self, class: &Class) -> bool {
+        self.class.is_subclass_of(class)
     }
 
-    pub fn set(&mut self, name: Token, value: Literal) {
+    pub fn set(
+        &mut self,
+        name: Token,
+        value: Literal,
+        interpreter: &mut dyn Runnable,
+    ) -> InterpreterResult<()> {
         if let Some(name) = name.lexeme {
-            self.fields.borrow_mut().insert(name, value);
+            if let Some(Literal::Callable(setter)) = self.class.get_setter(name.as_str()) {
+                setter.bind(self.clone()).call(interpreter, vec![value])?;
+            } else {
+                self.fields.borrow_mut().insert(name, value);
+            }
         }
+        Ok(())
     }
 }
 
 impl Display for Instance {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} instance", self.class.to_string())
+        write!(f, "{} instance", self.class)
+    }
+}
+
+// Clones share their fields, so the fields allocation identifies the object
+impl PartialEq for Instance {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.fields, &other.fields)
+    }
+}
+
+impl Hash for Instance {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Rc::as_ptr(&self.fields).hash(state);
     }
 }
 
 impl Clone for Instance {
     fn clone(&self) -> Self {
         Instance {
-            class: self.class.clone(),
+            class: Rc::clone(&self.class),
             fields: Rc::clone(&self.fields),
         }
     }
@@ -1,5 +1,10 @@
+use crate::ast::{Expr, Pattern, Stmt};
+use crate::environment::Environment;
+use crate::literal::Literal;
+use crate::parser::Parser;
 use crate::scanner;
 use crate::token::{Token, TokenType};
+use utils::errors::{InterpreterError, ScannerError};
 
 #[test]
 fn test_scanner() {
@@ -8,8 +13,262 @@ fn test_scanner() {
         Ok(it) => it,
         _ => return,
     };
-    let mut expected_tokens = Vec::new();
-    expected_tokens.push(Token::new(TokenType::And, Some("and".to_string()), None, 1));
-    expected_tokens.push(Token::new(TokenType::EOF, None, None, 1));
+    let expected_tokens = vec![
+        Token::new(TokenType::And, Some("and".to_string()), None, 1, 1),
+        Token::new(TokenType::EOF, None, None, 1, 4),
+    ];
     assert_eq!(tokens, expected_tokens);
 }
+
+#[test]
+fn test_scanner_block_comments() {
+    let mut scanner =
+        scanner::Scanner::new("/* outer /* inner */\n still outer */ and\n// trailing");
+    let tokens = scanner
+        .scan_tokens()
+        .expect("block comment should be skipped");
+    let expected_tokens = vec![
+        Token::new(TokenType::And, Some("and".to_string()), None, 2, 17),
+        Token::new(TokenType::EOF, None, None, 3, 12),
+    ];
+    assert_eq!(tokens, expected_tokens);
+}
+
+#[test]
+fn test_scanner_unterminated_block_comment() {
+    let mut scanner = scanner::Scanner::new("and\n/* /* */ never closed");
+    match scanner.scan_tokens() {
+        Err(ScannerError::UnterminatedComment(line)) => assert_eq!(line, 2),
+        res => panic!("Expected unterminated comment error, got {:?}", res),
+    }
+}
+
+#[test]
+fn test_scanner_string_escapes() {
+    let mut scanner = scanner::Scanner::new(r#""tab\tquote\"slash\\smile\u{1F600}""#);
+    let tokens = scanner.scan_tokens().expect("escapes should be valid");
+    assert_eq!(
+        tokens[0].literal,
+        Some(Literal::String(
+            "tab\tquote\"slash\\smile\u{1F600}".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_scanner_invalid_escape() {
+    let mut scanner = scanner::Scanner::new(r#""bad \q escape""#);
+    match scanner.scan_tokens() {
+        Err(ScannerError::InvalidEscape(escape, _)) => assert_eq!(escape, "\\q"),
+        res => panic!("Expected invalid escape error, got {:?}", res),
+    }
+}
+
+#[test]
+fn test_scanner_interpolation() {
+    let mut scanner = scanner::Scanner::new(r#""a ${ {} } b ${c}""#);
+    let token_types: Vec<TokenType> = scanner
+        .scan_tokens()
+        .expect("interpolation should scan")
+        .into_iter()
+        .map(|t| t.token_type)
+        .collect();
+    use TokenType::*;
+    assert_eq!(
+        token_types,
+        vec![
+            Interpolation,
+            LeftBrace,
+            RightBrace,
+            Interpolation,
+            Identifier,
+            String,
+            EOF
+        ]
+    );
+}
+
+#[test]
+fn test_scanner_number_literals() {
+    let mut scanner =
+        scanner::Scanner::new("0xFF 0b1010 1e-9 2.5E3 1_000_000 1. 100000000000000000000");
+    let numbers: Vec<String> = scanner
+        .scan_tokens()
+        .expect("number literals should scan")
+        .into_iter()
+        .filter(|t| t.token_type == TokenType::Number)
+        .map(|t| format!("{:?}", t.literal.unwrap()))
+        .collect();
+    let expected = vec![
+        "Integer(255)",
+        "Integer(10)",
+        "Number(1e-9)",
+        "Number(2500.0)",
+        "Integer(1000000)",
+        "Integer(1)",
+        "Number(1e20)",
+    ];
+    assert_eq!(numbers, expected);
+}
+
+#[test]
+fn test_scanner_malformed_number_literals() {
+    for source in &[
+        "0xFG",
+        "0b102",
+        "0x",
+        "1e",
+        "1e+",
+        "1__0",
+        "1_",
+        "1.5_",
+        "12abc",
+        "0x1_0000_0000_0000_0000",
+    ] {
+        let mut scanner = scanner::Scanner::new(source);
+        match scanner.scan_tokens() {
+            Err(ScannerError::MalformedNumber(lexeme, ..)) => assert_eq!(&lexeme, source),
+            res => panic!("Expected malformed number for {}, got {:?}", source, res),
+        }
+    }
+}
+
+fn parse_expr(source: &str) -> Expr {
+    let tokens = scanner::Scanner::new(source).scan_tokens().unwrap();
+    match Parser::new(tokens).parse().unwrap().pop() {
+        Some(Stmt::Expr(expr)) => expr,
+        stmt => panic!("Expected expression statement, got {:?}", stmt),
+    }
+}
+
+#[test]
+fn test_parser_conditional_precedence() {
+    // Binds looser than `or`, tighter than assignment, and nests to the right
+    let expr = parse_expr("x = a or b ? c : d ? e : f;");
+    let Expr::Assign(_, value, _) = expr else {
+        panic!("Expected assignment, got {:?}", expr);
+    };
+    let Expr::Conditional(condition, _, _, else_branch) = *value else {
+        panic!("Expected conditional");
+    };
+    assert!(matches!(*condition, Expr::Logical(..)));
+    assert!(matches!(*else_branch, Expr::Conditional(..)));
+}
+
+#[test]
+fn test_parser_comma_operator() {
+    // Commas inside argument lists separate arguments
+    let expr = parse_expr("f(a, b), [c, d], e;");
+    let Expr::Comma(left, _, right) = expr else {
+        panic!("Expected comma, got {:?}", expr);
+    };
+    assert!(matches!(*right, Expr::Variable(..)));
+    let Expr::Comma(call, _, list) = *left else {
+        panic!("Expected comma");
+    };
+    assert!(matches!(*call, Expr::Call(_, _, ref args) if args.len() == 2));
+    assert!(matches!(*list, Expr::List(ref elements) if elements.len() == 2));
+}
+
+#[test]
+fn test_parser_match_statement() {
+    let source = "match (x) { case 1, -2.5 => print x; case Point => {} default => print 0; }";
+    let tokens = scanner::Scanner::new(source).scan_tokens().unwrap();
+    let stmt = Parser::new(tokens).parse().unwrap().pop();
+    let Some(Stmt::Match(_, _, cases, Some(_))) = stmt else {
+        panic!("Expected match with default, got {:?}", stmt);
+    };
+    let literals: Vec<(usize, &Literal)> = cases[0]
+        .0
+        .iter()
+        .filter_map(|pattern| match pattern {
+            Pattern::Literal(token, literal) => Some((token.column, literal)),
+            Pattern::Class(_) => None,
+        })
+        .collect();
+    // A negated pattern starts at its minus sign
+    assert_eq!(
+        literals,
+        [(18, &Literal::Integer(1)), (21, &Literal::Number(-2.5))]
+    );
+    assert!(matches!(
+        cases[1].0[..],
+        [Pattern::Class(Expr::Variable(..))]
+    ));
+
+    // The default case has to be the last one
+    let source = "match (x) { default => print 0; case 1 => print 1; }";
+    let tokens = scanner::Scanner::new(source).scan_tokens().unwrap();
+    assert!(Parser::new(tokens).parse().is_err());
+}
+
+#[test]
+fn test_parser_recovers_before_statements() {
+    // Recovery stops at the statement after the first error, so the error inside it is
+    // reported at its own line rather than at the closing brace it leaves behind
+    for source in &[
+        "var a = + 1\ntry {\n    a = ;\n} catch (e) {}",
+        "var a = + 1\nmatch (a) {\n    case 1 => a = ;\n}",
+    ] {
+        let tokens = scanner::Scanner::new(source).scan_tokens().unwrap();
+        let errors = Parser::new(tokens).parse().unwrap_err();
+        let lines: Vec<usize> = errors.iter().map(|e| e.line()).collect();
+        assert_eq!(lines[..2], [1, 3], "{}", source);
+    }
+}
+
+#[test]
+fn test_environment_checks_resolved_slots() {
+    let name =
+        |lexeme: &str| Token::new(TokenType::Identifier, Some(lexeme.to_string()), None, 3, 1);
+    let globals = Environment::new(None).into_cell();
+    let mut outer = Environment::new(Some(globals));
+    outer.define("a".to_string(), Literal::Integer(1));
+    let mut inner = Environment::new(Some(outer.into_cell()));
+    inner.define("b".to_string(), Literal::Integer(2));
+
+    assert_eq!(
+        inner.get_at(1, 0, &name("a")).ok(),
+        Some(Literal::Integer(1))
+    );
+    assert!(inner.assign_at(0, 0, &name("b"), Literal::Nil).is_ok());
+    assert_eq!(inner.local_distance("a"), Some(1));
+
+    // A slot holding another variable, and a depth past the end of the chain
+    assert!(matches!(
+        inner.get_at(0, 0, &name("a")),
+        Err(InterpreterError::UnresolvedLocal(ref n, 0, 3)) if n == "a"
+    ));
+    assert!(matches!(
+        inner.assign_at(5, 0, &name("a"), Literal::Nil),
+        Err(InterpreterError::UnresolvedLocal(_, 5, 3))
+    ));
+}
+
+#[test]
+fn test_literal_integer_float_equality() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    let hash = |literal: &Literal| {
+        let mut hasher = DefaultHasher::new();
+        literal.hash(&mut hasher);
+        hasher.finish()
+    };
+
+    let big = 1_i64 << 53;
+    // 2^53 + 1 rounds to 2^53 as a float, but the values differ
+    assert_ne!(Literal::Integer(big + 1), Literal::Number(big as f64));
+    assert!(Literal::Integer(big + 1) > Literal::Number(big as f64));
+    assert_eq!(Literal::Integer(big), Literal::Number(big as f64));
+    assert_eq!(
+        hash(&Literal::Integer(big)),
+        hash(&Literal::Number(big as f64))
+    );
+    assert_eq!(hash(&Literal::Integer(0)), hash(&Literal::Number(-0.0)));
+    assert_ne!(Literal::Integer(i64::MAX), Literal::Number(i64::MAX as f64));
+    assert!(Literal::Integer(1) < Literal::Number(1.5));
+    assert!(Literal::Integer(-1) > Literal::Number(-1.5));
+
+    assert!(!Literal::Number(f64::NAN).is_hashable());
+    assert!(Literal::Number(f64::INFINITY).is_hashable());
+}
@@ -4,7 +4,10 @@ pub mod class;
 pub mod environment;
 pub mod function;
 pub mod instance;
+pub mod list;
 pub mod literal;
+pub mod map;
+pub mod module;
 pub mod runnable;
 pub mod scanner;
 pub mod token;
@@ -0,0 +1,114 @@
+use crate::callable::Callable;
+use crate::instance::Instance;
+use crate::literal::Literal;
+use crate::runnable::{EarlyReturn, InterpreterResult, Runnable};
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::rc::Rc;
+use utils::errors::InterpreterError;
+
+// Lists are shared by reference, so mutations are visible through every alias
+pub type List = Rc<RefCell<Vec<Literal>>>;
+
+#[derive(Debug, Clone, Copy)]
+enum Method {
+    Push,
+    Pop,
+    Len,
+    Insert,
+    Remove,
+    Slice,
+}
+
+// Built-in list method, bound to the list it was accessed on
+#[derive(Debug, Clone)]
+pub struct ListMethod {
+    method: Method,
+    list: List,
+}
+
+pub fn new_list(elements: Vec<Literal>) -> List {
+    Rc::new(RefCell::new(elements))
+}
+
+pub fn get_method(list: &List, name: &str) -> Option<Literal> {
+    let method = match name {
+        "push" => Method::Push,
+        "pop" => Method::Pop,
+        "len" => Method::Len,
+        "insert" => Method::Insert,
+        "remove" => Method::Remove,
+        "slice" => Method::Slice,
+        _ => return None,
+    };
+    Some(Literal::Callable(Box::new(ListMethod {
+        method,
+        list: Rc::clone(list),
+    })))
+}
+
+// Converts a subscript into a position in a list of length `len`. With `inclusive`
+// the position one past the end is also allowed, e.g. for insert and slice bounds.
+pub fn to_index(index: Literal, len: usize, inclusive: bool) -> InterpreterResult<usize> {
+    let index = i64::try_from(index)?;
+    let limit = if inclusive { len + 1 } else { len };
+    match usize::try_from(index) {
+        Ok(i) if i < limit => Ok(i),
+        _ => Err(EarlyReturn::Error(InterpreterError::IndexOutOfBounds(
+            index, len,
+        ))),
+    }
+}
+
+impl Callable for ListMethod {
+    fn arity(&self) -> usize {
+        match self.method {
+            Method::Pop | Method::Len => 0,
+            Method::Push | Method::Remove => 1,
+            Method::Insert | Method::Slice => 2,
+        }
+    }
+
+    fn call(
+        &self,
+        _interpreter: &mut dyn Runnable,
+        args: Vec<Literal>,
+    ) -> InterpreterResult<Literal> {
+        let mut args = args.into_iter();
+        let mut list = self.list.borrow_mut();
+        match self.method {
+            Method::Push => {
+                list.push(args.next().unwrap_or(Literal::Nil));
+                Ok(Literal::Nil)
+            }
+            Method::Pop => Ok(list.pop().unwrap_or(Literal::Nil)),
+            Method::Len => Ok(Literal::Integer(list.len() as i64)),
+            Method::Insert => {
+                let index = to_index(args.next().unwrap_or(Literal::Nil), list.len(), true)?;
+                list.insert(index, args.next().unwrap_or(Literal::Nil));
+                Ok(Literal::Nil)
+            }
+            Method::Remove => {
+                let index = to_index(args.next().unwrap_or(Literal::Nil), list.len(), false)?;
+                Ok(list.remove(index))
+            }
+            Method::Slice => {
+                let start = to_index(args.next().unwrap_or(Literal::Nil), list.len(), true)?;
+                let end = to_index(args.next().unwrap_or(Literal::Nil), list.len(), true)?;
+                let slice = list.get(start..end).ok_or(EarlyReturn::Error(
+                    InterpreterError::InvalidSlice(start, end),
+                ))?;
+                Ok(Literal::List(new_list(slice.to_vec())))
+            }
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn Callable> {
+        Box::new(self.clone())
+    }
+
+    fn bind(&self, _instance: Instance) -> Box<dyn Callable> {
+        // HACK noop, list methods are already bound to their list
+        self.box_clone()
+    }
+}
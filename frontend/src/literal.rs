@@ -1,65 +1,225 @@
 use crate::callable::Callable;
 use crate::class::Class;
 use crate::instance::Instance;
+use crate::list::List;
+use crate::map::Map;
+use crate::module::Module;
 use crate::runnable::EarlyReturn;
 use std::cmp::{Ordering, PartialEq, PartialOrd};
 use std::convert::TryFrom;
 use std::fmt::Display;
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
 use utils::errors::InterpreterError;
+use utils::number::Number;
 
 #[derive(Debug, Clone)]
 pub enum Literal {
     String(String),
     Number(f64),
+    Integer(i64),
     Boolean(bool),
     Nil,
     Callable(Box<dyn Callable>),
-    Class(Class),
+    Class(Rc<Class>),
     Instance(Instance),
+    List(List),
+    Map(Map),
+    Module(Module),
 }
 
-impl Hash for Literal {
-    fn hash<H: std::hash::Hasher>(&self, _state: &mut H) {
-        // Noop, since literals do not need to get resolved
+impl Literal {
+    // Value types, and objects compared by identity, can be used as map keys. NaN is
+    // not equal to itself, so it could never be found again.
+    pub fn is_hashable(&self) -> bool {
+        match self {
+            Literal::Number(n) => !n.is_nan(),
+            _ => matches!(
+                self,
+                Literal::String(_)
+                    | Literal::Integer(_)
+                    | Literal::Boolean(_)
+                    | Literal::Nil
+                    | Literal::Class(_)
+                    | Literal::Instance(_)
+            ),
+        }
+    }
+}
+
+// Lower bound of i64 and upper bound (exclusive), both exactly representable as floats
+const I64_MIN: f64 = -9_223_372_036_854_775_808.0;
+const I64_END: f64 = 9_223_372_036_854_775_808.0;
+
+// Compares the exact values, converting the integer instead would round it above 2^53
+fn compare_integer(i: i64, n: f64) -> Option<Ordering> {
+    if n.is_nan() {
+        None
+    } else if n >= I64_END {
+        Some(Ordering::Less)
+    } else if n < I64_MIN {
+        Some(Ordering::Greater)
+    } else {
+        // Ties on the integer part are decided by the sign of the fraction
+        let fraction = n.fract();
+        Some(
+            i.cmp(&(n.trunc() as i64))
+                .then_with(|| 0.0.partial_cmp(&fraction).unwrap()),
+        )
     }
 }
 
-impl Eq for Literal {
-    fn assert_receiver_is_total_eq(&self) {
-        // Noop
+fn hash_number<H: Hasher>(n: f64, state: &mut H) {
+    // Whole floats equal an integer, so they hash like it. This also covers -0.0.
+    if n.fract() == 0.0 && (I64_MIN..I64_END).contains(&n) {
+        (n as i64).hash(state);
+    } else {
+        n.to_bits().hash(state);
+    }
+}
+
+impl Hash for Literal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Literal::String(s) => s.hash(state),
+            Literal::Number(n) => hash_number(*n, state),
+            Literal::Integer(i) => i.hash(state),
+            Literal::Boolean(b) => b.hash(state),
+            Literal::Class(c) => Rc::as_ptr(c).hash(state),
+            Literal::Instance(i) => i.hash(state),
+            // Other reference types only hash their kind, equality decides the rest
+            _ => std::mem::discriminant(self).hash(state),
+        }
     }
 }
 
+impl Eq for Literal {}
+
 impl Display for Literal {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_nested(f, &mut Vec::new())
+    }
+}
+
+// Allows equality checks on Expr
+impl PartialEq for Literal {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_nested(other, &mut Vec::new())
+    }
+}
+
+// Lists and maps can contain themselves, so the ones being printed or compared are kept
+// to stop at a cycle instead of following it forever
+type Visiting = Vec<*const ()>;
+
+fn enter<T>(visiting: &mut Visiting, value: &Rc<T>) -> bool {
+    let pointer = Rc::as_ptr(value) as *const ();
+    if visiting.contains(&pointer) {
+        return false;
+    }
+    visiting.push(pointer);
+    true
+}
+
+impl Literal {
+    // A list or map already being printed shows as `[...]` or `{...}`
+    fn fmt_nested(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        visiting: &mut Visiting,
+    ) -> std::fmt::Result {
         match self {
             Self::String(str) => write!(f, "{}", str),
             Self::Number(fl) => write!(f, "{}", fl),
+            Self::Integer(i) => write!(f, "{}", i),
             Self::Boolean(b) => write!(f, "{}", b),
             Self::Nil => write!(f, "Nil"),
             Self::Callable(_c) => write!(f, "Callable"),
             Self::Class(c) => write!(f, "class {}", c),
             Self::Instance(i) => write!(f, "{}", i),
+            Self::List(l) => {
+                if !enter(visiting, l) {
+                    return write!(f, "[...]");
+                }
+                write!(f, "[")?;
+                for (i, element) in l.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    element.fmt_nested(f, visiting)?;
+                }
+                visiting.pop();
+                write!(f, "]")
+            }
+            Self::Map(m) => {
+                if !enter(visiting, m) {
+                    return write!(f, "{{...}}");
+                }
+                write!(f, "{{")?;
+                for (i, (key, value)) in m.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: ", key)?;
+                    value.fmt_nested(f, visiting)?;
+                }
+                visiting.pop();
+                write!(f, "}}")
+            }
+            Self::Module(m) => write!(f, "{}", m),
         }
     }
-}
 
-// Allows equality checks on Expr
-impl PartialEq for Literal {
-    fn eq(&self, other: &Self) -> bool {
+    // A list or map met again while comparing its own elements is only equal to itself
+    fn eq_nested(&self, other: &Self, visiting: &mut Visiting) -> bool {
         match (self, other) {
-            (&Literal::String(ref s), &Literal::String(ref o)) => (s == o),
-            (&Literal::Number(ref s), &Literal::Number(ref o)) => (s == o),
-            (&Literal::Boolean(ref s), &Literal::Boolean(ref o)) => (s == o),
+            (Literal::String(s), Literal::String(o)) => s == o,
+            (Literal::Number(s), Literal::Number(o)) => s == o,
+            (Literal::Integer(s), Literal::Integer(o)) => s == o,
+            // Integers and floats compare numerically
+            (Literal::Integer(s), Literal::Number(o)) => {
+                compare_integer(*s, *o) == Some(Ordering::Equal)
+            }
+            (Literal::Number(s), Literal::Integer(o)) => {
+                compare_integer(*o, *s) == Some(Ordering::Equal)
+            }
+            (Literal::Boolean(s), Literal::Boolean(o)) => s == o,
             (&Literal::Nil, &Literal::Nil) => true,
-            (&Literal::Instance(ref i), &Literal::Instance(ref j)) => {
-                // check for referential equality
-                std::ptr::eq(i, j)
+            // Objects are only equal to themselves
+            (Literal::Instance(i), Literal::Instance(j)) => i == j,
+            (Literal::Class(i), Literal::Class(j)) => Rc::ptr_eq(i, j),
+            // Lists compare element-wise
+            (Literal::List(l), Literal::List(o)) => {
+                if Rc::ptr_eq(l, o) {
+                    return true;
+                }
+                if !enter(visiting, l) {
+                    return false;
+                }
+                let (l, o) = (l.borrow(), o.borrow());
+                let equal = l.len() == o.len()
+                    && l.iter()
+                        .zip(o.iter())
+                        .all(|(l, o)| l.eq_nested(o, visiting));
+                visiting.pop();
+                equal
             }
-            (&Literal::Class(ref i), &Literal::Class(ref j)) => {
-                // check for referential equality
-                std::ptr::eq(i, j)
+            (Literal::Module(m), Literal::Module(o)) => m == o,
+            // Maps are equal when they hold the same entries, regardless of order
+            (Literal::Map(m), Literal::Map(o)) => {
+                if Rc::ptr_eq(m, o) {
+                    return true;
+                }
+                if !enter(visiting, m) {
+                    return false;
+                }
+                let (m, o) = (m.borrow(), o.borrow());
+                let equal = m.len() == o.len()
+                    && m.iter().all(|(key, value)| {
+                        o.get(key).is_some_and(|o| value.eq_nested(o, visiting))
+                    });
+                visiting.pop();
+                equal
             }
             _ => false,
         }
@@ -70,12 +230,17 @@ impl PartialEq for Literal {
 impl PartialOrd for Literal {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         match (self, other) {
-            (&Literal::String(ref s), &Literal::String(ref o)) => s.partial_cmp(o),
-            (&Literal::Number(ref s), &Literal::Number(ref o)) => s.partial_cmp(o),
-            (&Literal::Boolean(ref s), &Literal::Boolean(ref o)) => s.partial_cmp(o),
+            (Literal::String(s), Literal::String(o)) => s.partial_cmp(o),
+            (Literal::Number(s), Literal::Number(o)) => s.partial_cmp(o),
+            (Literal::Integer(s), Literal::Integer(o)) => s.partial_cmp(o),
+            (Literal::Integer(s), Literal::Number(o)) => compare_integer(*s, *o),
+            (Literal::Number(s), Literal::Integer(o)) => {
+                compare_integer(*o, *s).map(Ordering::reverse)
+            }
+            (Literal::Boolean(s), Literal::Boolean(o)) => s.partial_cmp(o),
             (&Literal::Nil, &Literal::Nil) => Some(Ordering::Equal),
-            (&Literal::Instance(ref _i), &Literal::Instance(ref _j)) => None,
-            (&Literal::Class(ref _i), &Literal::Class(ref _j)) => None,
+            (Literal::Instance(_i), Literal::Instance(_j)) => None,
+            (Literal::Class(_i), Literal::Class(_j)) => None,
             _ => None,
         }
     }
@@ -85,11 +250,26 @@ impl TryFrom<Literal> for f64 {
     type Error = EarlyReturn;
 
     fn try_from(value: Literal) -> Result<Self, Self::Error> {
-        if let Literal::Number(n) = value {
-            Ok(n)
+        match value {
+            Literal::Number(n) => Ok(n),
+            // Integers are promoted when mixed with floats
+            Literal::Integer(i) => Ok(i as f64),
+            _ => Err(EarlyReturn::Error(InterpreterError::InvalidCoercion(
+                "Unable to coerce into number".to_string(),
+            ))),
+        }
+    }
+}
+
+impl TryFrom<Literal> for i64 {
+    type Error = EarlyReturn;
+
+    fn try_from(value: Literal) -> Result<Self, Self::Error> {
+        if let Literal::Integer(i) = value {
+            Ok(i)
         } else {
             Err(EarlyReturn::Error(InterpreterError::InvalidCoercion(
-                "Unable to coerce into number".to_string(),
+                "Unable to coerce into integer".to_string(),
             )))
         }
     }
@@ -125,6 +305,15 @@ impl TryFrom<TryFromWrapper<Literal>> for bool {
     }
 }
 
+impl From<Number> for Literal {
+    fn from(number: Number) -> Self {
+        match number {
+            Number::Integer(i) => Literal::Integer(i),
+            Number::Float(n) => Literal::Number(n),
+        }
+    }
+}
+
 impl From<Literal> for bool {
     fn from(value: Literal) -> Self {
         match value {
@@ -0,0 +1,154 @@
+use crate::callable::Callable;
+use crate::instance::Instance;
+use crate::list::new_list;
+use crate::literal::Literal;
+use crate::runnable::{EarlyReturn, InterpreterResult, Runnable};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use utils::errors::InterpreterError;
+
+// Maps are shared by reference, like lists
+pub type Map = Rc<RefCell<OrderedMap>>;
+
+// Hash map that remembers insertion order, so printing and iterating is deterministic
+#[derive(Debug, Clone, Default)]
+pub struct OrderedMap {
+    entries: Vec<(Literal, Literal)>,
+    #[allow(clippy::mutable_key_type)]
+    index: HashMap<Literal, usize>,
+}
+
+impl OrderedMap {
+    pub fn get(&self, key: &Literal) -> Option<&Literal> {
+        self.index.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn insert(&mut self, key: Literal, value: Literal) {
+        match self.index.get(&key) {
+            Some(&i) => self.entries[i].1 = value,
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &Literal) -> Option<Literal> {
+        let i = self.index.remove(key)?;
+        for position in self.index.values_mut() {
+            if *position > i {
+                *position -= 1;
+            }
+        }
+        Some(self.entries.remove(i).1)
+    }
+
+    pub fn contains_key(&self, key: &Literal) -> bool {
+        self.index.contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn keys(&self) -> Vec<Literal> {
+        self.entries.iter().map(|(k, _)| k.clone()).collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(Literal, Literal)> {
+        self.entries.iter()
+    }
+
+    pub fn values(&self) -> Vec<Literal> {
+        self.entries.iter().map(|(_, v)| v.clone()).collect()
+    }
+}
+
+pub fn new_map(entries: Vec<(Literal, Literal)>) -> InterpreterResult<Map> {
+    let mut map = OrderedMap::default();
+    for (key, value) in entries {
+        map.insert(check_key(key)?, value);
+    }
+    Ok(Rc::new(RefCell::new(map)))
+}
+
+pub fn check_key(key: Literal) -> InterpreterResult<Literal> {
+    if key.is_hashable() {
+        Ok(key)
+    } else {
+        Err(EarlyReturn::Error(InterpreterError::UnhashableKey(
+            key.to_string(),
+        )))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Method {
+    Keys,
+    Values,
+    Has,
+    Delete,
+    Len,
+}
+
+// Built-in map method, bound to the map it was accessed on
+#[derive(Debug, Clone)]
+pub struct MapMethod {
+    method: Method,
+    map: Map,
+}
+
+pub fn get_method(map: &Map, name: &str) -> Option<Literal> {
+    let method = match name {
+        "keys" => Method::Keys,
+        "values" => Method::Values,
+        "has" => Method::Has,
+        "delete" => Method::Delete,
+        "len" => Method::Len,
+        _ => return None,
+    };
+    Some(Literal::Callable(Box::new(MapMethod {
+        method,
+        map: Rc::clone(map),
+    })))
+}
+
+impl Callable for MapMethod {
+    fn arity(&self) -> usize {
+        match self.method {
+            Method::Keys | Method::Values | Method::Len => 0,
+            Method::Has | Method::Delete => 1,
+        }
+    }
+
+    fn call(
+        &self,
+        _interpreter: &mut dyn Runnable,
+        args: Vec<Literal>,
+    ) -> InterpreterResult<Literal> {
+        let key = args.into_iter().next().unwrap_or(Literal::Nil);
+        let mut map = self.map.borrow_mut();
+        match self.method {
+            Method::Keys => Ok(Literal::List(new_list(map.keys()))),
+            Method::Values => Ok(Literal::List(new_list(map.values()))),
+            Method::Has => Ok(Literal::Boolean(map.contains_key(&key))),
+            // Returns the removed value, or nil when the key was missing
+            Method::Delete => Ok(map.remove(&key).unwrap_or(Literal::Nil)),
+            Method::Len => Ok(Literal::Integer(map.len() as i64)),
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn Callable> {
+        Box::new(self.clone())
+    }
+
+    fn bind(&self, _instance: Instance) -> Box<dyn Callable> {
+        // HACK noop, map methods are already bound to their map
+        self.box_clone()
+    }
+}
@@ -0,0 +1,48 @@
+use crate::environment::Environment;
+use crate::literal::Literal;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::rc::Rc;
+
+// A loaded .lox file, exposing its exported declarations as fields
+#[derive(Debug, Clone)]
+pub struct Module {
+    path: String,
+    environment: Rc<RefCell<Environment>>,
+    exports: Rc<HashSet<String>>,
+}
+
+impl Module {
+    pub fn new(
+        path: String,
+        environment: Rc<RefCell<Environment>>,
+        exports: HashSet<String>,
+    ) -> Self {
+        Module {
+            path,
+            environment,
+            exports: Rc::new(exports),
+        }
+    }
+
+    // Reads from the module scope, so later changes to an export are visible
+    pub fn get(&self, name: &str) -> Option<Literal> {
+        if !self.exports.contains(name) {
+            return None;
+        }
+        self.environment.borrow().values.get(name).cloned()
+    }
+}
+
+impl PartialEq for Module {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.environment, &other.environment)
+    }
+}
+
+impl Display for Module {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<module {}>", self.path)
+    }
+}
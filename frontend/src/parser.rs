@@ -1,6 +1,7 @@
-use crate::ast::{Expr, Stmt};
+use crate::ast::{Expr, MethodKind, NodeId, Pattern, Signature, Stmt};
 use crate::literal::Literal;
 use crate::token::{Token, TokenType};
+use std::rc::Rc;
 use utils::errors::ParserError;
 
 pub type ParserResult<T> = Result<T, ParserError>;
@@ -15,22 +16,35 @@ impl Parser {
         Parser { tokens, current: 0 }
     }
 
-    pub fn parse(&mut self) -> ParserResult<Vec<Stmt>> {
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, Vec<ParserError>> {
         let mut statements = Vec::new();
+        let mut errors = Vec::new();
         while !self.is_end() {
-            if let Ok(decl) = self.declaration() {
-                statements.push(decl);
+            match self.declaration() {
+                Ok(decl) => statements.push(decl),
+                Err(err) => errors.push(err),
             }
         }
-        Ok(statements)
+
+        if errors.is_empty() {
+            Ok(statements)
+        } else {
+            Err(errors)
+        }
     }
 
     // AST NODE Fns
     fn declaration(&mut self) -> ParserResult<Stmt> {
         let res;
-        if self.match_token(vec![TokenType::Class]) {
+        if self.match_token(vec![TokenType::Export]) {
+            res = self.export_declaration();
+        } else if self.match_token(vec![TokenType::Import]) {
+            res = self.import_declaration();
+        } else if self.match_token(vec![TokenType::Class]) {
             res = self.class_declaration();
-        } else if self.match_token(vec![TokenType::Fun]) {
+        } else if self.check(TokenType::Fun) && !self.check_next(TokenType::LeftParen) {
+            // `fun (` starts an anonymous function expression instead
+            self.advance();
             res = self.function("function");
         } else if self.match_token(vec![TokenType::Var]) {
             res = self.var_declaration();
@@ -44,13 +58,49 @@ impl Parser {
         res
     }
 
+    fn export_declaration(&mut self) -> ParserResult<Stmt> {
+        let declaration = if self.match_token(vec![TokenType::Class]) {
+            self.class_declaration()?
+        } else if self.match_token(vec![TokenType::Fun]) {
+            self.function("function")?
+        } else if self.match_token(vec![TokenType::Var]) {
+            self.var_declaration()?
+        } else {
+            return Err(ParserError::UnexpectedToken(
+                "Expect declaration after 'export'".to_string(),
+                self.peek().line,
+            ));
+        };
+        Ok(Stmt::Export(Box::new(declaration)))
+    }
+
+    fn import_declaration(&mut self) -> ParserResult<Stmt> {
+        let keyword = self.previous().clone();
+        let path = match self.consume(TokenType::String, "Expect module path after 'import'")? {
+            Token {
+                literal: Some(Literal::String(path)),
+                ..
+            } => path,
+            token => {
+                return Err(ParserError::UnexpectedToken(
+                    "Expect module path after 'import'".to_string(),
+                    token.line,
+                ))
+            }
+        };
+        self.consume(TokenType::As, "Expect 'as' after module path")?;
+        let name = self.consume(TokenType::Identifier, "Expect module name after 'as'")?;
+        self.consume(TokenType::SemiColon, "Expect ';' after import")?;
+        Ok(Stmt::Import(keyword, path, name))
+    }
+
     fn class_declaration(&mut self) -> ParserResult<Stmt> {
         let name = self.consume(TokenType::Identifier, "Expected class name")?;
 
         let mut super_class = None;
         if self.match_token(vec![TokenType::Less]) {
             self.consume(TokenType::Identifier, "Expected superclass name")?;
-            super_class = Some(Expr::Variable(self.previous().clone()));
+            super_class = Some(Expr::Variable(self.previous().clone(), NodeId::next()));
         }
 
         self.consume(TokenType::LeftBrace, "Expected '{' before class body")?;
@@ -58,15 +108,46 @@ impl Parser {
         let mut methods = Vec::new();
 
         while !self.check(TokenType::RightBrace) && !self.is_end() {
-            methods.push(self.function("method")?);
+            if self.match_token(vec![TokenType::Class]) {
+                methods.push((MethodKind::Static, self.function("static method")?));
+            } else {
+                methods.push(self.method()?);
+            }
         }
         self.consume(TokenType::RightBrace, "Expected '}' after class body")?;
 
         Ok(Stmt::Class(name, super_class, methods))
     }
 
+    // Parses a method, getter `name {}` or setter `name=(value) {}`
+    fn method(&mut self) -> ParserResult<(MethodKind, Stmt)> {
+        let name = self.consume(TokenType::Identifier, "Expect method name")?;
+        if self.match_token(vec![TokenType::LeftBrace]) {
+            let body = self.block()?;
+            return Ok((
+                MethodKind::Getter,
+                Stmt::Function(name, Vec::new(), body.into(), Signature::default()),
+            ));
+        }
+        let kind = if self.match_token(vec![TokenType::Equal]) {
+            MethodKind::Setter
+        } else {
+            MethodKind::Method
+        };
+        self.consume(TokenType::LeftParen, "Expect '(' after method name")?;
+        let (params, body, signature) = self.function_body("method")?;
+        if kind == MethodKind::Setter && params.len() != 1 {
+            return Err(ParserError::GenericError(
+                "Setters must take exactly one parameter".to_string(),
+                name.line,
+            ));
+        }
+        Ok((kind, Stmt::Function(name, params, body, signature)))
+    }
+
     fn var_declaration(&mut self) -> ParserResult<Stmt> {
         let name = self.consume(TokenType::Identifier, "Expected variable name")?;
+        let annotation = self.type_annotation()?;
 
         let mut init = None;
         if self.match_token(vec![TokenType::Equal]) {
@@ -77,7 +158,21 @@ impl Parser {
             TokenType::SemiColon,
             "expected ';' after variable declaration",
         )?;
-        Ok(Stmt::Var(name, init))
+        Ok(Stmt::Var(name, annotation, init))
+    }
+
+    // Parses an optional `: Type` annotation
+    fn type_annotation(&mut self) -> ParserResult<Option<Token>> {
+        if !self.match_token(vec![TokenType::Colon]) {
+            return Ok(None);
+        }
+        if self.match_token(vec![TokenType::Identifier, TokenType::Nil]) {
+            return Ok(Some(self.previous().clone()));
+        }
+        Err(ParserError::UnexpectedToken(
+            "Expect type name after ':'".to_string(),
+            self.peek().line,
+        ))
     }
 
     fn function(&mut self, kind: &str) -> ParserResult<Stmt> {
@@ -89,13 +184,21 @@ impl Parser {
             TokenType::LeftParen,
             format!("Expect '(' after {} name", kind).as_str(),
         )?;
+        let (params, body, signature) = self.function_body(kind)?;
+        Ok(Stmt::Function(name, params, body, signature))
+    }
+
+    // Parses the parameters, return type and body of a function, after its opening '('
+    fn function_body(&mut self, kind: &str) -> ParserResult<(Vec<Token>, Rc<[Stmt]>, Signature)> {
         let mut params = Vec::new();
+        let mut signature = Signature::default();
         if !self.check(TokenType::RightParen) {
             loop {
                 if params.len() >= 255 {
-                    return Err(ParserError::ArgumentCountExceeded);
+                    return Err(ParserError::ArgumentCountExceeded(self.peek().line));
                 }
                 params.push(self.consume(TokenType::Identifier, "Expect param name")?);
+                signature.params.push(self.type_annotation()?);
 
                 if !self.match_token(vec![TokenType::Comma]) {
                     break;
@@ -104,6 +207,7 @@ impl Parser {
         }
 
         self.consume(TokenType::RightParen, "Expect ')' after parameters")?;
+        signature.return_type = self.type_annotation()?;
 
         self.consume(
             TokenType::LeftBrace,
@@ -112,7 +216,7 @@ impl Parser {
 
         let body = self.block()?;
 
-        Ok(Stmt::Function(name, params, body))
+        Ok((params, body.into(), signature))
     }
 
     fn statement(&mut self) -> ParserResult<Stmt> {
@@ -131,14 +235,133 @@ impl Parser {
         if self.match_token(vec![TokenType::While]) {
             return self.while_statement();
         }
+        if self.match_token(vec![TokenType::Break, TokenType::Continue]) {
+            return self.loop_jump_statement();
+        }
+        if self.match_token(vec![TokenType::Throw]) {
+            let keyword = self.previous().clone();
+            let value = self.expression()?;
+            self.consume(TokenType::SemiColon, "Expect ';' after thrown value")?;
+            return Ok(Stmt::Throw(keyword, value));
+        }
+        if self.match_token(vec![TokenType::Try]) {
+            return self.try_statement();
+        }
+        if self.match_token(vec![TokenType::Match]) {
+            return self.match_statement();
+        }
         if self.match_token(vec![TokenType::LeftBrace]) {
             return Ok(Stmt::Block(self.block()?));
         }
         self.expression_statement()
     }
 
+    // match (subject) { case 1, 2 => stmt case Class => stmt default => stmt }
+    fn match_statement(&mut self) -> ParserResult<Stmt> {
+        let keyword = self.previous().clone();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'match'")?;
+        let subject = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after match subject")?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before match cases")?;
+
+        let mut cases = Vec::new();
+        let mut default = None;
+        while !self.check(TokenType::RightBrace) && !self.is_end() {
+            if self.match_token(vec![TokenType::Default]) {
+                if default.is_some() {
+                    return Err(ParserError::GenericError(
+                        "A match can only have one default case".to_string(),
+                        self.previous().line,
+                    ));
+                }
+                self.consume(TokenType::FatArrow, "Expect '=>' after 'default'")?;
+                default = Some(Box::new(self.statement()?));
+                continue;
+            }
+            self.consume(TokenType::Case, "Expect 'case' or 'default' in match")?;
+            if default.is_some() {
+                return Err(ParserError::GenericError(
+                    "The default case must come last".to_string(),
+                    self.previous().line,
+                ));
+            }
+            let mut patterns = vec![self.pattern()?];
+            while self.match_token(vec![TokenType::Comma]) {
+                patterns.push(self.pattern()?);
+            }
+            self.consume(TokenType::FatArrow, "Expect '=>' after case patterns")?;
+            cases.push((patterns, self.statement()?));
+        }
+        self.consume(TokenType::RightBrace, "Expect '}' after match cases")?;
+
+        Ok(Stmt::Match(keyword, subject, cases, default))
+    }
+
+    // A literal, optionally negated number, or a class name
+    fn pattern(&mut self) -> ParserResult<Pattern> {
+        if self.match_token(vec![TokenType::Identifier]) {
+            return Ok(Pattern::Class(Expr::Variable(
+                self.previous().clone(),
+                NodeId::next(),
+            )));
+        }
+        let start = self.peek().clone();
+        let negate = self.match_token(vec![TokenType::Minus]);
+        let literal = match self.primary()? {
+            Expr::Literal(literal) => literal,
+            _ => return Err(Self::invalid_pattern(self.previous().line)),
+        };
+        if !negate {
+            return Ok(Pattern::Literal(start, literal));
+        }
+        match literal {
+            Literal::Integer(i) => Ok(Pattern::Literal(start, Literal::Integer(-i))),
+            Literal::Number(n) => Ok(Pattern::Literal(start, Literal::Number(-n))),
+            _ => Err(Self::invalid_pattern(self.previous().line)),
+        }
+    }
+
+    fn invalid_pattern(line: usize) -> ParserError {
+        ParserError::GenericError(
+            "Expect a literal or class name as pattern".to_string(),
+            line,
+        )
+    }
+
+    fn try_statement(&mut self) -> ParserResult<Stmt> {
+        let line = self.previous().line;
+        self.consume(TokenType::LeftBrace, "Expect '{' after 'try'")?;
+        let body = self.block()?;
+
+        let mut catch = None;
+        if self.match_token(vec![TokenType::Catch]) {
+            self.consume(TokenType::LeftParen, "Expect '(' after 'catch'")?;
+            let name = self.consume(TokenType::Identifier, "Expect catch variable name")?;
+            self.consume(TokenType::RightParen, "Expect ')' after catch variable")?;
+            self.consume(TokenType::LeftBrace, "Expect '{' after catch clause")?;
+            catch = Some((name, self.block()?));
+        }
+
+        let mut finally = None;
+        if self.match_token(vec![TokenType::Finally]) {
+            self.consume(TokenType::LeftBrace, "Expect '{' after 'finally'")?;
+            finally = Some(self.block()?);
+        }
+
+        if catch.is_none() && finally.is_none() {
+            return Err(ParserError::GenericError(
+                "Expect 'catch' or 'finally' after try block".to_string(),
+                line,
+            ));
+        }
+        Ok(Stmt::Try(body, catch, finally))
+    }
+
     fn for_statement(&mut self) -> ParserResult<Stmt> {
         self.consume(TokenType::LeftParen, "Expect '(' after 'for'")?;
+        if self.check(TokenType::Identifier) && self.check_next(TokenType::In) {
+            return self.for_in_statement();
+        }
         let init;
         if self.match_token(vec![TokenType::SemiColon]) {
             init = None;
@@ -162,14 +385,12 @@ impl Parser {
 
         let mut body = self.statement()?;
 
-        if let Some(increment) = increment {
-            body = Stmt::Block(vec![body, Stmt::Expr(increment)]);
-        }
-
         if condition.is_none() {
             condition = Some(Expr::Literal(Literal::Boolean(true)));
         }
-        body = Stmt::While(condition.unwrap(), Box::new(body));
+        // The increment is kept on the loop rather than appended to the body so that
+        // `continue` still runs it
+        body = Stmt::While(condition.unwrap(), Box::new(body), increment);
 
         if let Some(init) = init {
             body = Stmt::Block(vec![init, body]);
@@ -178,12 +399,21 @@ impl Parser {
         Ok(body)
     }
 
+    fn for_in_statement(&mut self) -> ParserResult<Stmt> {
+        let name = self.advance().clone();
+        self.advance();
+        let iterable = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after for-in clause")?;
+        let body = self.statement()?;
+        Ok(Stmt::ForIn(name, iterable, Box::new(body)))
+    }
+
     fn while_statement(&mut self) -> ParserResult<Stmt> {
         self.consume(TokenType::LeftParen, "Expect '(' after 'while'")?;
         let condition = self.expression()?;
         self.consume(TokenType::RightParen, "Expect ')' after condition")?;
         let body = self.statement()?;
-        Ok(Stmt::While(condition, Box::new(body)))
+        Ok(Stmt::While(condition, Box::new(body), None))
     }
 
     fn if_statement(&mut self) -> ParserResult<Stmt> {
@@ -208,6 +438,17 @@ impl Parser {
         Ok(Stmt::Return(keyword, value))
     }
 
+    fn loop_jump_statement(&mut self) -> ParserResult<Stmt> {
+        let keyword = self.previous().clone();
+        if keyword.token_type == TokenType::Break {
+            self.consume(TokenType::SemiColon, "Expect ';' after 'break'")?;
+            Ok(Stmt::Break(keyword))
+        } else {
+            self.consume(TokenType::SemiColon, "Expect ';' after 'continue'")?;
+            Ok(Stmt::Continue(keyword))
+        }
+    }
+
     fn block(&mut self) -> ParserResult<Vec<Stmt>> {
         let mut stmts = Vec::new();
 
@@ -232,25 +473,88 @@ impl Parser {
     }
 
     fn expression(&mut self) -> ParserResult<Expr> {
-        self.assignment()
+        self.comma()
+    }
+
+    // Arguments and collection elements are parsed with `assignment` so their commas
+    // are not taken as comma operators
+    fn comma(&mut self) -> ParserResult<Expr> {
+        let mut expr = self.assignment()?;
+        while self.match_token(vec![TokenType::Comma]) {
+            let comma = self.previous().clone();
+            let right = self.assignment()?;
+            expr = Expr::Comma(Box::new(expr), comma, Box::new(right));
+        }
+        Ok(expr)
     }
 
     fn assignment(&mut self) -> ParserResult<Expr> {
-        let expr = self.or()?;
+        let expr = self.conditional()?;
 
         if self.match_token(vec![TokenType::Equal]) {
+            let equals = self.previous().line;
             let value = self.assignment()?;
 
             return match expr {
-                Expr::Variable(name) => Ok(Expr::Assign(name, Box::new(value))),
+                Expr::Variable(name, id) => Ok(Expr::Assign(name, Box::new(value), id)),
                 Expr::Get(obj, field_name) => Ok(Expr::Set(obj, field_name, Box::new(value))),
-                _ => Err(ParserError::InvalidAssignmentTarget),
+                Expr::Subscript(obj, bracket, index) => {
+                    Ok(Expr::SetSubscript(obj, bracket, index, Box::new(value)))
+                }
+                _ => Err(ParserError::InvalidAssignmentTarget(equals)),
             };
         }
 
+        use TokenType::*;
+        if self.match_token(vec![
+            PlusEqual,
+            MinusEqual,
+            StarEqual,
+            SlashEqual,
+            PercentEqual,
+        ]) {
+            let operator = self.previous().clone();
+            let value = self.assignment()?;
+            let target = Self::update_target(expr, operator.line)?;
+            return Ok(Expr::CompoundAssign(
+                Box::new(target),
+                operator,
+                Box::new(value),
+            ));
+        }
+
         Ok(expr)
     }
 
+    // Compound assignment and increments read and write the same place
+    fn update_target(expr: Expr, line: usize) -> ParserResult<Expr> {
+        match expr {
+            Expr::Variable(..) | Expr::Get(..) | Expr::Subscript(..) => Ok(expr),
+            _ => Err(ParserError::InvalidAssignmentTarget(line)),
+        }
+    }
+
+    // Right associative, so `a ? b : c ? d : e` is `a ? b : (c ? d : e)`
+    fn conditional(&mut self) -> ParserResult<Expr> {
+        let condition = self.or()?;
+        if self.match_token(vec![TokenType::Question]) {
+            let question = self.previous().clone();
+            let then_branch = self.expression()?;
+            self.consume(
+                TokenType::Colon,
+                "Expect ':' after then branch of conditional",
+            )?;
+            let else_branch = self.conditional()?;
+            return Ok(Expr::Conditional(
+                Box::new(condition),
+                question,
+                Box::new(then_branch),
+                Box::new(else_branch),
+            ));
+        }
+        Ok(condition)
+    }
+
     fn or(&mut self) -> ParserResult<Expr> {
         let mut expr = self.and()?;
         while self.match_token(vec![TokenType::Or]) {
@@ -283,17 +587,59 @@ impl Parser {
     }
 
     fn comparison(&mut self) -> ParserResult<Expr> {
-        let mut expr = self.term()?;
+        let mut expr = self.bitwise_or()?;
 
         use TokenType::*;
         while self.match_token(vec![Greater, GreaterEqual, Less, LessEqual]) {
             let operator = self.previous().clone();
-            let right = self.term()?;
+            let right = self.bitwise_or()?;
             expr = Expr::Binary(Box::new(expr), operator, Box::new(right))
         }
         Ok(expr)
     }
 
+    fn bitwise_or(&mut self) -> ParserResult<Expr> {
+        let mut expr = self.bitwise_xor()?;
+        while self.match_token(vec![TokenType::Pipe]) {
+            let operator = self.previous().clone();
+            let right = self.bitwise_xor()?;
+            expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn bitwise_xor(&mut self) -> ParserResult<Expr> {
+        let mut expr = self.bitwise_and()?;
+        while self.match_token(vec![TokenType::Caret]) {
+            let operator = self.previous().clone();
+            let right = self.bitwise_and()?;
+            expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn bitwise_and(&mut self) -> ParserResult<Expr> {
+        let mut expr = self.shift()?;
+        while self.match_token(vec![TokenType::Ampersand]) {
+            let operator = self.previous().clone();
+            let right = self.shift()?;
+            expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn shift(&mut self) -> ParserResult<Expr> {
+        let mut expr = self.term()?;
+
+        use TokenType::*;
+        while self.match_token(vec![LessLess, GreaterGreater]) {
+            let operator = self.previous().clone();
+            let right = self.term()?;
+            expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
+        }
+        Ok(expr)
+    }
+
     fn term(&mut self) -> ParserResult<Expr> {
         let mut expr = self.factor()?;
 
@@ -310,7 +656,7 @@ impl Parser {
         let mut expr = self.unary()?;
 
         use TokenType::*;
-        while self.match_token(vec![Slash, Star]) {
+        while self.match_token(vec![Slash, Star, Percent, TildeSlash]) {
             let operator = self.previous().clone();
             let right = self.unary()?;
             expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
@@ -325,7 +671,22 @@ impl Parser {
             let right = self.unary()?;
             return Ok(Expr::Unary(operator, Box::new(right)));
         }
-        self.call()
+        if self.match_token(vec![PlusPlus, MinusMinus]) {
+            let operator = self.previous().clone();
+            let target = Self::update_target(self.unary()?, operator.line)?;
+            return Ok(Expr::Increment(Box::new(target), operator, true));
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> ParserResult<Expr> {
+        let expr = self.call()?;
+        if self.match_token(vec![TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let operator = self.previous().clone();
+            let target = Self::update_target(expr, operator.line)?;
+            return Ok(Expr::Increment(Box::new(target), operator, false));
+        }
+        Ok(expr)
     }
 
     fn call(&mut self) -> ParserResult<Expr> {
@@ -337,6 +698,10 @@ impl Parser {
                 let name =
                     self.consume(TokenType::Identifier, "Expected property name after '.'")?;
                 expr = Expr::Get(Box::new(expr), name);
+            } else if self.match_token(vec![TokenType::LeftBracket]) {
+                let index = self.expression()?;
+                let bracket = self.consume(TokenType::RightBracket, "Expect ']' after index")?;
+                expr = Expr::Subscript(Box::new(expr), bracket, Box::new(index));
             } else {
                 break;
             }
@@ -349,9 +714,9 @@ impl Parser {
         if !self.check(TokenType::RightParen) {
             loop {
                 if args.len() >= 255 {
-                    return Err(ParserError::ArgumentCountExceeded);
+                    return Err(ParserError::ArgumentCountExceeded(self.peek().line));
                 }
-                args.push(self.expression()?);
+                args.push(self.assignment()?);
                 if !self.match_token(vec![TokenType::Comma]) {
                     break;
                 }
@@ -375,8 +740,10 @@ impl Parser {
             return Ok(Expr::Literal(Literal::Nil));
         }
         if self.match_token(vec![Number]) {
-            if let Literal::Number(f) = self.previous().literal.as_ref().unwrap() {
-                return Ok(Expr::Literal(Literal::Number(*f)));
+            match self.previous().literal.as_ref().unwrap() {
+                Literal::Number(f) => return Ok(Expr::Literal(Literal::Number(*f))),
+                Literal::Integer(i) => return Ok(Expr::Literal(Literal::Integer(*i))),
+                _ => {}
             }
             // TODO dont unwrap early?
         }
@@ -385,29 +752,106 @@ impl Parser {
                 return Ok(Expr::Literal(Literal::String(s.to_string())));
             }
         }
+        if self.match_token(vec![Interpolation]) {
+            return self.interpolation();
+        }
         if self.match_token(vec![Super]) {
             let keyword = self.previous().clone();
             self.consume(TokenType::Dot, "Expected '.' after 'super'")?;
             let method = self.consume(TokenType::Identifier, "Expect superclass method name")?;
-            return Ok(Expr::Super(keyword, method));
+            return Ok(Expr::Super(keyword, method, NodeId::next()));
         }
         if self.match_token(vec![This]) {
-            return Ok(Expr::This(self.previous().clone()));
+            return Ok(Expr::This(self.previous().clone(), NodeId::next()));
         }
         if self.match_token(vec![Identifier]) {
-            return Ok(Expr::Variable(self.previous().clone()));
+            return Ok(Expr::Variable(self.previous().clone(), NodeId::next()));
         }
         if self.match_token(vec![LeftParen]) {
             let expr = self.expression()?;
-            //self.con
+            self.consume(RightParen, "Expect ')' after expression")?;
             return Ok(Expr::Grouping(Box::new(expr)));
         }
+        if self.match_token(vec![Fun]) {
+            let keyword = self.previous().clone();
+            self.consume(LeftParen, "Expect '(' after 'fun'")?;
+            let (params, body, signature) = self.function_body("lambda")?;
+            return Ok(Expr::Lambda(keyword, params, body, signature));
+        }
+        if self.match_token(vec![LeftBracket]) {
+            return self.list();
+        }
+        if self.match_token(vec![LeftBrace]) {
+            return self.map();
+        }
         Err(ParserError::UnexpectedToken(
             "Expected expression".to_string(),
             self.peek().line,
         ))
     }
 
+    fn list(&mut self) -> ParserResult<Expr> {
+        let mut elements = Vec::new();
+        // Allows a trailing comma after the last element
+        while !self.check(TokenType::RightBracket) {
+            elements.push(self.assignment()?);
+            if !self.match_token(vec![TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(TokenType::RightBracket, "Expect ']' after list elements")?;
+        Ok(Expr::List(elements))
+    }
+
+    fn map(&mut self) -> ParserResult<Expr> {
+        let mut entries = Vec::new();
+        while !self.check(TokenType::RightBrace) {
+            let key = self.assignment()?;
+            self.consume(TokenType::Colon, "Expect ':' after map key")?;
+            entries.push((key, self.assignment()?));
+            if !self.match_token(vec![TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(TokenType::RightBrace, "Expect '}' after map entries")?;
+        Ok(Expr::Map(entries))
+    }
+
+    // Desugars "a ${b} c" into the concatenation ("a " + str(b)) + " c", so embedded
+    // values of any type are printed while '+' itself only joins strings. Empty segments
+    // after an embedded expression are left out.
+    fn interpolation(&mut self) -> ParserResult<Expr> {
+        let mut expr = self.string_segment();
+        loop {
+            let previous = self.previous();
+            let plus = Token::new(TokenType::Plus, None, None, previous.line, previous.column);
+            let embedded = Expr::Stringify(Box::new(self.expression()?));
+            expr = Expr::Binary(Box::new(expr), plus.clone(), Box::new(embedded));
+
+            let finished = !self.match_token(vec![TokenType::Interpolation]);
+            if finished {
+                self.consume(
+                    TokenType::String,
+                    "Expect '}' after interpolated expression",
+                )?;
+            }
+            match self.string_segment() {
+                Expr::Literal(Literal::String(ref segment)) if segment.is_empty() => {}
+                segment => expr = Expr::Binary(Box::new(expr), plus, Box::new(segment)),
+            }
+            if finished {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn string_segment(&self) -> Expr {
+        match self.previous().literal.as_ref() {
+            Some(Literal::String(s)) => Expr::Literal(Literal::String(s.to_string())),
+            _ => Expr::Literal(Literal::String(std::string::String::new())),
+        }
+    }
+
     // MISC UTILS FNs
     fn match_token(&mut self, token_types: Vec<TokenType>) -> bool {
         for t in token_types {
@@ -426,6 +870,12 @@ impl Parser {
         self.peek().token_type == token_type
     }
 
+    fn check_next(&self, token_type: TokenType) -> bool {
+        self.tokens
+            .get(self.current + 1)
+            .is_some_and(|t| t.token_type == token_type)
+    }
+
     fn advance(&mut self) -> &Token {
         if !self.is_end() {
             self.current += 1;
@@ -467,7 +917,8 @@ impl Parser {
             }
 
             match self.peek().token_type {
-                Class | For | Fun | If | Print | Return | Var | While => return,
+                Break | Class | Continue | Export | For | Fun | If | Import | Match | Print
+                | Return | Throw | Try | Var | While => return,
                 _ => (),
             };
 
@@ -6,19 +6,23 @@ use crate::environment::Environment;
 use crate::literal::Literal;
 use utils::errors::InterpreterError;
 
-type InterpreterResult<T> = Result<T, EarlyReturn>;
+pub type InterpreterResult<T> = Result<T, EarlyReturn>;
 
 #[derive(Debug)]
 pub enum EarlyReturn {
     Error(InterpreterError),
     Return(Literal),
+    Break,
+    Continue,
+    // A value thrown by `throw`, unwinding to the nearest enclosing catch
+    Throw(Literal),
 }
 
 // Abstract behaviour that interpreters and compilers should implement
 pub trait Runnable {
     fn block(
         &mut self,
-        body: Vec<Stmt>,
+        body: &[Stmt],
         environment: Rc<RefCell<Environment>>,
     ) -> InterpreterResult<()>;
 }
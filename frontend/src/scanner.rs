@@ -1,20 +1,22 @@
 use std::collections::HashMap;
 use std::{iter, str};
 use utils::errors::ScannerError;
+use utils::number::parse_number;
 
 use crate::literal::Literal;
 use crate::token::{Token, TokenType};
 
-/*
- * TODO: Block style comments
- */
-
 pub type ScannerResult<T> = Result<T, ScannerError>;
 
 pub struct Scanner<'a> {
     source: iter::Peekable<str::Chars<'a>>,
     line: usize,
+    // Column of the last consumed character, and of the first one of the current token
+    column: usize,
+    token_column: usize,
     keywords: HashMap<&'static str, TokenType>,
+    // Brace depth inside each currently open string interpolation
+    interpolations: Vec<usize>,
 }
 
 impl<'a> Scanner<'a> {
@@ -22,26 +24,42 @@ impl<'a> Scanner<'a> {
         let mut keywords: HashMap<&str, TokenType> = HashMap::new();
         use crate::token::TokenType::*;
         keywords.insert("and", And);
+        keywords.insert("as", As);
+        keywords.insert("break", Break);
+        keywords.insert("case", Case);
+        keywords.insert("catch", Catch);
         keywords.insert("class", Class);
+        keywords.insert("continue", Continue);
+        keywords.insert("default", Default);
         keywords.insert("else", Else);
+        keywords.insert("export", Export);
         keywords.insert("false", False);
+        keywords.insert("finally", Finally);
         keywords.insert("for", For);
         keywords.insert("fun", Fun);
         keywords.insert("if", If);
+        keywords.insert("import", Import);
+        keywords.insert("in", In);
+        keywords.insert("match", Match);
         keywords.insert("nil", Nil);
         keywords.insert("or", Or);
         keywords.insert("print", Print);
         keywords.insert("return", Return);
         keywords.insert("super", Super);
         keywords.insert("this", This);
+        keywords.insert("throw", Throw);
         keywords.insert("true", True);
+        keywords.insert("try", Try);
         keywords.insert("var", Var);
         keywords.insert("while", While);
 
         Scanner {
             source: source.chars().peekable(),
             line: 1,
+            column: 0,
+            token_column: 0,
             keywords,
+            interpolations: Vec::new(),
         }
     }
 
@@ -49,14 +67,18 @@ impl<'a> Scanner<'a> {
         let mut tokens: Vec<Token> = Vec::new();
         loop {
             self.skip_whitespace();
-            if let Some(c) = self.source.next() {
-                if !self.skip_comments(c) {
+            if let Some(c) = self.advance() {
+                self.token_column = self.column;
+                if !self.skip_comments(c)? {
                     match self.scan_token(c) {
                         Ok(token) => tokens.push(token),
                         Err(err) => return Err(err),
                     }
                 }
+            } else if !self.interpolations.is_empty() {
+                return Err(ScannerError::UntermiantedString(self.line));
             } else {
+                self.token_column = self.column + 1;
                 tokens.push(self.make_token(TokenType::EOF));
                 break;
             }
@@ -65,8 +87,24 @@ impl<'a> Scanner<'a> {
         Ok(tokens)
     }
 
+    fn peek_next(&self) -> Option<char> {
+        let mut lookahead = self.source.clone();
+        lookahead.next();
+        lookahead.next()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.source.next();
+        match c {
+            Some('\n') => self.column = 0,
+            Some(_) => self.column += 1,
+            None => {}
+        }
+        c
+    }
+
     fn make_token(&self, token_type: TokenType) -> Token {
-        Token::new(token_type, None, None, self.line)
+        Token::new(token_type, None, None, self.line, self.token_column)
     }
 
     fn skip_whitespace(&mut self) {
@@ -77,21 +115,57 @@ impl<'a> Scanner<'a> {
                 self.line += 1;
             }
 
-            self.source.next();
+            self.advance();
         }
     }
 
-    fn skip_comments(&mut self, c: char) -> bool {
-        if c == '/' && self.source.peek() == Some(&'/') {
-            while let Some(&c) = self.source.peek() {
-                self.source.next();
-                if c == '\n' {
-                    self.line += 1;
-                    return true;
+    fn skip_comments(&mut self, c: char) -> ScannerResult<bool> {
+        if c != '/' {
+            return Ok(false);
+        }
+
+        match self.source.peek() {
+            Some(&'/') => {
+                while let Some(c) = self.advance() {
+                    if c == '\n' {
+                        self.line += 1;
+                        break;
+                    }
                 }
+                Ok(true)
             }
+            Some(&'*') => {
+                self.advance();
+                self.skip_block_comment()?;
+                Ok(true)
+            }
+            _ => Ok(false),
         }
-        false
+    }
+
+    // Block comments can be nested, so track how many are still open
+    fn skip_block_comment(&mut self) -> ScannerResult<()> {
+        let start_line = self.line;
+        let mut depth = 1;
+        while let Some(c) = self.advance() {
+            match c {
+                '\n' => self.line += 1,
+                '/' if self.source.peek() == Some(&'*') => {
+                    self.advance();
+                    depth += 1;
+                }
+                '*' if self.source.peek() == Some(&'/') => {
+                    self.advance();
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Err(ScannerError::UnterminatedComment(start_line))
     }
 
     fn scan_token(&mut self, c: char) -> ScannerResult<Token> {
@@ -100,28 +174,78 @@ impl<'a> Scanner<'a> {
         match c {
             '(' => Ok(self.make_token(LeftParen)),
             ')' => Ok(self.make_token(RightParen)),
-            '{' => Ok(self.make_token(LeftBrace)),
-            '}' => Ok(self.make_token(RightBrace)),
+            '{' => {
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                Ok(self.make_token(LeftBrace))
+            }
+            '}' => match self.interpolations.last_mut() {
+                // Closes an interpolated expression, resume scanning the string
+                Some(0) => {
+                    self.interpolations.pop();
+                    self.scan_string()
+                }
+                Some(depth) => {
+                    *depth -= 1;
+                    Ok(self.make_token(RightBrace))
+                }
+                None => Ok(self.make_token(RightBrace)),
+            },
+            '[' => Ok(self.make_token(LeftBracket)),
+            ']' => Ok(self.make_token(RightBracket)),
             ',' => Ok(self.make_token(Comma)),
             '.' => Ok(self.make_token(Dot)),
-            '-' => Ok(self.make_token(Minus)),
-            '+' => Ok(self.make_token(Plus)),
+            '-' if self.source.peek() == Some(&'-') => {
+                self.advance();
+                Ok(self.make_token(MinusMinus))
+            }
+            '+' if self.source.peek() == Some(&'+') => {
+                self.advance();
+                Ok(self.make_token(PlusPlus))
+            }
+            '-' => Ok(self.scan_operator(Minus, MinusEqual)),
+            '+' => Ok(self.scan_operator(Plus, PlusEqual)),
             ';' => Ok(self.make_token(SemiColon)),
-            '*' => Ok(self.make_token(Star)),
+            ':' => Ok(self.make_token(Colon)),
+            '?' => Ok(self.make_token(Question)),
+            '*' => Ok(self.scan_operator(Star, StarEqual)),
+            '%' => Ok(self.scan_operator(Percent, PercentEqual)),
+            '&' => Ok(self.make_token(Ampersand)),
+            '|' => Ok(self.make_token(Pipe)),
+            '^' => Ok(self.make_token(Caret)),
+
+            // Integer division, '//' is already taken by line comments
+            '~' if self.source.peek() == Some(&'/') => {
+                self.advance();
+                Ok(self.make_token(TildeSlash))
+            }
 
             // Could either be comment or slash
-            '/' => Ok(self.make_token(Slash)),
+            '/' => Ok(self.scan_operator(Slash, SlashEqual)),
 
             // Need to peek ahead to check for next char
+            '=' if self.source.peek() == Some(&'>') => {
+                self.advance();
+                Ok(self.make_token(FatArrow))
+            }
             '=' => Ok(self.scan_operator(Equal, EqualEqual)),
             '!' => Ok(self.scan_operator(Bang, BangEqual)),
+            '<' if self.source.peek() == Some(&'<') => {
+                self.advance();
+                Ok(self.make_token(LessLess))
+            }
+            '>' if self.source.peek() == Some(&'>') => {
+                self.advance();
+                Ok(self.make_token(GreaterGreater))
+            }
             '<' => Ok(self.scan_operator(Less, LessEqual)),
             '>' => Ok(self.scan_operator(Greater, GreaterEqual)),
 
             '"' => self.scan_string(),
 
             c => {
-                if c.is_digit(10) {
+                if c.is_ascii_digit() {
                     self.scan_number(c)
                 } else if c.is_alphabetic() || c == '_' {
                     self.scan_identifier(c)
@@ -134,62 +258,127 @@ impl<'a> Scanner<'a> {
 
     fn scan_operator(&mut self, inequality_type: TokenType, equality_type: TokenType) -> Token {
         if self.source.peek() == Some(&'=') {
-            self.source.next();
+            self.advance();
             self.make_token(equality_type)
         } else {
             self.make_token(inequality_type)
         }
     }
 
+    // Scans up to the closing quote, or up to the next '${' which is emitted as an
+    // Interpolation token so the parser can splice in the embedded expression
     fn scan_string(&mut self) -> ScannerResult<Token> {
         let mut captured_string = String::new();
         let start_line = self.line;
-        while let Some(&c) = self.source.peek() {
-            if c == '"' {
-                self.source.next();
-                return Ok(Token::new(
-                    TokenType::String,
-                    Some(captured_string.clone()),
-                    Some(Literal::String(captured_string)),
-                    self.line,
-                ));
-            } else if c == '\n' {
-                self.line += 1;
+        while let Some(c) = self.advance() {
+            match c {
+                '"' => return Ok(self.make_string_token(TokenType::String, captured_string)),
+                '$' if self.source.peek() == Some(&'{') => {
+                    self.advance();
+                    self.interpolations.push(0);
+                    return Ok(self.make_string_token(TokenType::Interpolation, captured_string));
+                }
+                '\\' => captured_string.push(self.scan_escape()?),
+                '\n' => {
+                    self.line += 1;
+                    captured_string.push(c);
+                }
+                c => captured_string.push(c),
             }
-            captured_string.push(self.source.next().unwrap());
         }
 
         Err(ScannerError::UntermiantedString(start_line))
     }
 
+    fn scan_escape(&mut self) -> ScannerResult<char> {
+        match self.advance() {
+            Some('n') => Ok('\n'),
+            Some('t') => Ok('\t'),
+            Some('r') => Ok('\r'),
+            Some('0') => Ok('\0'),
+            Some('"') => Ok('"'),
+            Some('\\') => Ok('\\'),
+            Some('$') => Ok('$'),
+            Some('u') => self.scan_unicode_escape(),
+            Some(c) => Err(ScannerError::InvalidEscape(format!("\\{}", c), self.line)),
+            None => Err(ScannerError::InvalidEscape("\\".to_string(), self.line)),
+        }
+    }
+
+    // Unicode escapes take the form \u{1F600}
+    fn scan_unicode_escape(&mut self) -> ScannerResult<char> {
+        let mut escape = String::from("\\u");
+        if self.source.peek() == Some(&'{') {
+            escape.push(self.advance().unwrap());
+            let mut hex = String::new();
+            while let Some(&c) = self.source.peek() {
+                if !c.is_ascii_hexdigit() {
+                    break;
+                }
+                hex.push(c);
+                self.advance();
+            }
+            escape.push_str(hex.as_str());
+
+            if self.source.peek() == Some(&'}') {
+                escape.push(self.advance().unwrap());
+                let code_point = u32::from_str_radix(hex.as_str(), 16).ok();
+                if let Some(c) = code_point
+                    .filter(|_| hex.len() <= 6)
+                    .and_then(char::from_u32)
+                {
+                    return Ok(c);
+                }
+            }
+        }
+
+        Err(ScannerError::InvalidEscape(escape, self.line))
+    }
+
+    fn make_string_token(&self, token_type: TokenType, captured_string: String) -> Token {
+        Token::new(
+            token_type,
+            Some(captured_string.clone()),
+            Some(Literal::String(captured_string)),
+            self.line,
+            self.token_column,
+        )
+    }
+
+    // Greedily captures everything that could belong to the literal so malformed
+    // input such as 0xFG or 1e is reported as one bad number rather than split tokens
     fn scan_number(&mut self, c: char) -> ScannerResult<Token> {
         let mut captured_number = String::new();
         captured_number.push(c);
+        let is_decimal = c != '0' || !matches!(self.source.peek(), Some('x' | 'X' | 'b' | 'B'));
 
         while let Some(&c) = self.source.peek() {
-            if c == '.' {
-                if captured_number.contains('.') {
-                    return Err(ScannerError::InvalidCharacter('.', self.line));
-                } else {
-                    captured_number.push(c)
-                }
-            } else if c.is_digit(10) {
-                captured_number.push(c);
-            } else {
+            let is_fraction = c == '.'
+                && is_decimal
+                && !captured_number.contains(['.', 'e', 'E'])
+                && self.peek_next().is_some_and(|n| n.is_ascii_digit());
+            let is_exponent_sign =
+                (c == '+' || c == '-') && is_decimal && captured_number.ends_with(['e', 'E']);
+            if !(c.is_alphanumeric() || c == '_' || is_fraction || is_exponent_sign) {
                 break;
             }
-            self.source.next();
+            captured_number.push(c);
+            self.advance();
         }
 
-        if let Ok(parsed_number) = captured_number.parse::<f64>() {
-            Ok(Token::new(
+        match parse_number(captured_number.as_str()) {
+            Ok(parsed_number) => Ok(Token::new(
                 TokenType::Number,
                 Some(captured_number),
-                Some(Literal::Number(parsed_number)),
+                Some(parsed_number.into()),
                 self.line,
-            ))
-        } else {
-            Err(ScannerError::InvalidTerm(captured_number, self.line))
+                self.token_column,
+            )),
+            Err(reason) => Err(ScannerError::MalformedNumber(
+                captured_number,
+                reason.to_string(),
+                self.line,
+            )),
         }
     }
 
@@ -198,11 +387,11 @@ impl<'a> Scanner<'a> {
         captured_identifier.push(c);
 
         while let Some(&c) = self.source.peek() {
-            if !c.is_alphabetic() && c != '_' && !c.is_digit(10) {
+            if !c.is_alphabetic() && c != '_' && !c.is_ascii_digit() {
                 break;
             }
             captured_identifier.push(c);
-            self.source.next();
+            self.advance();
         }
 
         match self.keywords.get(captured_identifier.as_str()) {
@@ -211,12 +400,14 @@ impl<'a> Scanner<'a> {
                 Some(captured_identifier),
                 None,
                 self.line,
+                self.token_column,
             )),
             None => Ok(Token::new(
                 TokenType::Identifier,
                 Some(captured_identifier),
                 None,
                 self.line,
+                self.token_column,
             )),
         }
     }
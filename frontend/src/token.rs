@@ -8,13 +8,21 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
     Minus,
     Plus,
     SemiColon,
+    Colon,
+    Question,
     Slash,
     Star,
+    Percent,
+    Ampersand,
+    Pipe,
+    Caret,
 
     // One or Two character tokens
     Bang,
@@ -25,27 +33,56 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    LessLess,
+    GreaterGreater,
+    TildeSlash,
+    // `=>` between match patterns and their body
+    FatArrow,
 
     // Literals
     Identifier,
     String,
     Number,
+    // String segment that is followed by an interpolated expression
+    Interpolation,
+
+    // Compound assignment and increment operators
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    PercentEqual,
+    PlusPlus,
+    MinusMinus,
 
     // Keywords
     And,
+    As,
+    Break,
+    Case,
+    Catch,
     Class,
+    Continue,
+    Default,
     Else,
+    Export,
     False,
+    Finally,
     Fun,
     For,
     If,
+    Import,
+    In,
+    Match,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
 
@@ -59,6 +96,7 @@ pub struct Token {
     pub lexeme: Option<String>,
     pub literal: Option<Literal>,
     pub line: usize,
+    pub column: usize,
 }
 
 impl Display for Token {
@@ -77,12 +115,14 @@ impl Token {
         lexeme: Option<String>,
         literal: Option<Literal>,
         line: usize,
+        column: usize,
     ) -> Self {
         Token {
             token_type,
             lexeme,
             literal,
             line,
+            column,
         }
     }
 }
@@ -0,0 +1,31 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use frontend::environment::Environment;
+use frontend::parser::Parser;
+use frontend::scanner::Scanner;
+use interpreter::interpreter::Interpreter;
+use interpreter::resolver::Resolver;
+use std::fs;
+
+// Recursive calls stress function call overhead, which used to copy the body each time
+fn fib(c: &mut Criterion) {
+    let source = fs::read_to_string("../__fixtures__/bench/fib.lox").expect("fixture");
+    let tokens = Scanner::new(&source).scan_tokens().expect("scan");
+    let ast = Parser::new(tokens).parse().expect("parse");
+    let mut resolver = Resolver::new();
+    resolver.resolve_stmts(&ast).expect("resolve");
+    let locals = resolver.into_locals();
+
+    let mut group = c.benchmark_group("fib");
+    group.sample_size(10);
+    group.bench_function("fib(25)", |b| {
+        b.iter(|| {
+            let mut interpreter = Interpreter::new(Environment::new(None));
+            interpreter.resolve(locals.clone());
+            interpreter.interpret(&ast).expect("run");
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, fib);
+criterion_main!(benches);
@@ -0,0 +1,622 @@
+use frontend::ast::{Expr, MethodKind, Pattern, Signature, Stmt};
+use frontend::literal::Literal;
+use frontend::token::{Token, TokenType};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct TypeError {
+    pub message: String,
+    pub line: usize,
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at line {}", self.message, self.line)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    // Unannotated values, anything goes
+    Any,
+    Nil,
+    Bool,
+    Number,
+    String,
+    List,
+    Map,
+    // Parameter types, when known, and the return type
+    Function(Option<Vec<Type>>, Box<Type>),
+    // The class object itself, called to create instances
+    Class(String),
+    Instance(String),
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::Any => write!(f, "Any"),
+            Type::Nil => write!(f, "Nil"),
+            Type::Bool => write!(f, "Bool"),
+            Type::Number => write!(f, "Number"),
+            Type::String => write!(f, "String"),
+            Type::List => write!(f, "List"),
+            Type::Map => write!(f, "Map"),
+            Type::Function(..) => write!(f, "Function"),
+            Type::Class(name) => write!(f, "class {}", name),
+            Type::Instance(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+struct Binding {
+    value_type: Type,
+    // Taken from the initializer rather than an annotation, so the variable may still
+    // be assigned values of another type
+    inferred: bool,
+}
+
+#[derive(Default)]
+struct ClassInfo {
+    super_class: Option<String>,
+    methods: HashMap<String, Type>,
+}
+
+// Checks optional type annotations. Unannotated parameters are `Any` and unannotated
+// variables take the type of their initializer until assigned something else, so
+// only code that opts in is held to its annotations, while calls to known functions
+// are still checked for their arity.
+pub struct TypeChecker {
+    scopes: Vec<HashMap<String, Binding>>,
+    classes: HashMap<String, ClassInfo>,
+    // Declared return types of the enclosing functions
+    return_types: Vec<Type>,
+    errors: Vec<TypeError>,
+    line: usize,
+}
+
+impl Default for TypeChecker {
+    fn default() -> Self {
+        TypeChecker::new()
+    }
+}
+
+impl TypeChecker {
+    pub fn new() -> Self {
+        TypeChecker {
+            scopes: vec![HashMap::new()],
+            classes: HashMap::new(),
+            return_types: Vec::new(),
+            errors: Vec::new(),
+            line: 0,
+        }
+    }
+
+    pub fn check(mut self, stmts: &[Stmt]) -> Result<(), Vec<TypeError>> {
+        self.check_stmts(stmts);
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    fn check_stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.check_stmt(stmt);
+        }
+    }
+
+    fn check_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Block(stmts) => self.block(stmts),
+            Stmt::Expr(expr) | Stmt::Print(expr) | Stmt::Throw(_, expr) => {
+                self.check_expr(expr);
+            }
+            Stmt::Var(name, annotation, init) => self.var_stmt(name, annotation, init),
+            Stmt::Function(name, params, body, signature) => {
+                let function = self.signature_type(signature);
+                self.declare(name, function.clone());
+                self.function(params, body, &function);
+            }
+            Stmt::If(condition, consequent, alternative) => {
+                self.check_expr(condition);
+                self.check_stmt(consequent);
+                if let Some(alternative) = alternative {
+                    self.check_stmt(alternative);
+                }
+            }
+            Stmt::Return(keyword, value) => self.return_stmt(keyword, value),
+            Stmt::While(condition, body, increment) => {
+                self.check_expr(condition);
+                self.check_stmt(body);
+                if let Some(increment) = increment {
+                    self.check_expr(increment);
+                }
+            }
+            Stmt::ForIn(name, iterable, body) => {
+                self.check_expr(iterable);
+                self.scoped(|checker| {
+                    checker.declare(name, Type::Any);
+                    checker.check_stmt(body);
+                });
+            }
+            Stmt::Class(name, super_class, methods) => self.class_stmt(name, super_class, methods),
+            Stmt::Break(_) | Stmt::Continue(_) => {}
+            Stmt::Import(_, _, name) => self.declare(name, Type::Any),
+            Stmt::Export(declaration) => self.check_stmt(declaration),
+            Stmt::Try(body, catch, finally) => {
+                self.block(body);
+                if let Some((name, handler)) = catch {
+                    self.scoped(|checker| {
+                        checker.declare(name, Type::Any);
+                        checker.check_stmts(handler);
+                    });
+                }
+                if let Some(finally) = finally {
+                    self.block(finally);
+                }
+            }
+            Stmt::Match(_, subject, cases, default) => {
+                self.check_expr(subject);
+                for (patterns, body) in cases {
+                    for pattern in patterns {
+                        if let Pattern::Class(class) = pattern {
+                            self.check_expr(class);
+                        }
+                    }
+                    self.check_stmt(body);
+                }
+                if let Some(default) = default {
+                    self.check_stmt(default);
+                }
+            }
+        }
+    }
+
+    fn block(&mut self, stmts: &[Stmt]) {
+        self.scoped(|checker| checker.check_stmts(stmts));
+    }
+
+    fn var_stmt(&mut self, name: &Token, annotation: &Option<Token>, init: &Option<Expr>) {
+        self.line = name.line;
+        let actual = init.as_ref().map(|init| self.check_expr(init));
+        if annotation.is_none() {
+            // A nil initializer is usually a placeholder for a later assignment
+            let inferred = match actual {
+                Some(Type::Nil) | None => Type::Any,
+                Some(actual) => actual,
+            };
+            self.bind(&lexeme(name), inferred, true);
+            return;
+        }
+
+        let declared = self.annotation_type(annotation);
+        if let Some(actual) = actual {
+            if !self.is_assignable(&declared, &actual) {
+                self.error(format!(
+                    "Cannot initialize variable '{}' of type {} with {}",
+                    lexeme(name),
+                    declared,
+                    actual
+                ));
+            }
+        }
+        self.declare(name, declared);
+    }
+
+    fn return_stmt(&mut self, keyword: &Token, value: &Option<Expr>) {
+        self.line = keyword.line;
+        let actual = match value {
+            Some(value) => self.check_expr(value),
+            None => Type::Nil,
+        };
+        let expected = self.return_types.last().cloned().unwrap_or(Type::Any);
+        if !self.is_assignable(&expected, &actual) {
+            self.error(format!(
+                "Expected to return {} but got {}",
+                expected, actual
+            ));
+        }
+    }
+
+    fn function(&mut self, params: &[Token], body: &[Stmt], function: &Type) {
+        let (param_types, return_type) = match function {
+            Type::Function(Some(params), return_type) => (params.clone(), *return_type.clone()),
+            _ => (Vec::new(), Type::Any),
+        };
+        self.return_types.push(return_type);
+        self.scoped(|checker| {
+            for (param, param_type) in params.iter().zip(param_types) {
+                checker.declare(param, param_type);
+            }
+            checker.check_stmts(body);
+        });
+        self.return_types.pop();
+    }
+
+    fn class_stmt(
+        &mut self,
+        name: &Token,
+        super_class: &Option<Expr>,
+        methods: &[(MethodKind, Stmt)],
+    ) {
+        let class_name = lexeme(name);
+        let mut info = ClassInfo::default();
+        if let Some(Expr::Variable(super_name, _)) = super_class {
+            info.super_class = Some(lexeme(super_name));
+        }
+        // The class is known before its signatures, which may refer to it
+        self.classes.insert(class_name.clone(), info);
+        self.declare(name, Type::Class(class_name.clone()));
+
+        // Signatures are collected first so methods can call each other
+        let mut method_types = HashMap::new();
+        for (kind, method) in methods {
+            if let (MethodKind::Method, Stmt::Function(name, _, _, signature)) = (kind, method) {
+                method_types.insert(lexeme(name), self.signature_type(signature));
+            }
+        }
+        if let Some(info) = self.classes.get_mut(&class_name) {
+            info.methods = method_types;
+        }
+
+        for (kind, method) in methods {
+            if let Stmt::Function(name, params, body, signature) = method {
+                let mut method_type = self.signature_type(signature);
+                // Initializers always return the instance
+                if *kind == MethodKind::Method && lexeme(name) == "init" {
+                    if let Type::Function(_, return_type) = &mut method_type {
+                        **return_type = Type::Any;
+                    }
+                }
+                self.scoped(|checker| {
+                    if *kind != MethodKind::Static {
+                        checker.define("this", Type::Instance(class_name.clone()));
+                    }
+                    checker.function(params, body, &method_type);
+                });
+            }
+        }
+    }
+
+    fn check_expr(&mut self, expr: &Expr) -> Type {
+        if let Some(line) = expr.line() {
+            self.line = line;
+        }
+        match expr {
+            Expr::Literal(literal) => match literal {
+                Literal::Integer(_) | Literal::Number(_) => Type::Number,
+                Literal::String(_) => Type::String,
+                Literal::Boolean(_) => Type::Bool,
+                Literal::Nil => Type::Nil,
+                _ => Type::Any,
+            },
+            Expr::Grouping(inner) => self.check_expr(inner),
+            Expr::Stringify(inner) => {
+                self.check_expr(inner);
+                Type::String
+            }
+            Expr::Variable(name, _) => self.lookup(&lexeme(name)),
+            Expr::This(..) => self.lookup("this"),
+            Expr::Super(..) => Type::Any,
+            Expr::Assign(name, value, _) => {
+                let actual = self.check_expr(value);
+                let name = lexeme(name);
+                match self.binding_mut(&name) {
+                    // Assigning another type to an inferred variable makes it `Any`
+                    Some(binding) if binding.inferred => {
+                        if binding.value_type != actual {
+                            binding.value_type = Type::Any;
+                        }
+                    }
+                    _ => {
+                        let declared = self.lookup(&name);
+                        if !self.is_assignable(&declared, &actual) {
+                            self.error(format!(
+                                "Cannot assign {} to variable '{}' of type {}",
+                                actual, name, declared
+                            ));
+                        }
+                    }
+                }
+                actual
+            }
+            Expr::Binary(left, operator, right) => {
+                let left = self.check_expr(left);
+                let right = self.check_expr(right);
+                self.binary(operator, left, right)
+            }
+            Expr::Logical(left, _, right) => {
+                let left = self.check_expr(left);
+                let right = self.check_expr(right);
+                if left == right {
+                    left
+                } else {
+                    Type::Any
+                }
+            }
+            Expr::Unary(operator, right) => {
+                let right = self.check_expr(right);
+                match operator.token_type {
+                    TokenType::Bang => Type::Bool,
+                    _ => {
+                        self.expect_number(&right);
+                        Type::Number
+                    }
+                }
+            }
+            Expr::Call(callee, _, args) => {
+                let callee = self.check_expr(callee);
+                let args: Vec<Type> = args.iter().map(|arg| self.check_expr(arg)).collect();
+                self.call(callee, args)
+            }
+            Expr::Get(object, name) => match self.check_expr(object) {
+                Type::Instance(class) => {
+                    self.find_method(&class, &lexeme(name)).unwrap_or(Type::Any)
+                }
+                _ => Type::Any,
+            },
+            Expr::Set(object, _, value) => {
+                self.check_expr(object);
+                self.check_expr(value)
+            }
+            Expr::Lambda(_, params, body, signature) => {
+                let function = self.signature_type(signature);
+                self.function(params, body, &function);
+                function
+            }
+            Expr::List(elements) => {
+                for element in elements {
+                    self.check_expr(element);
+                }
+                Type::List
+            }
+            Expr::Map(entries) => {
+                for (key, value) in entries {
+                    self.check_expr(key);
+                    self.check_expr(value);
+                }
+                Type::Map
+            }
+            Expr::Subscript(object, _, index) => {
+                let object = self.check_expr(object);
+                self.check_expr(index);
+                self.expect_subscriptable(&object);
+                Type::Any
+            }
+            Expr::SetSubscript(object, _, index, value) => {
+                let object = self.check_expr(object);
+                self.check_expr(index);
+                self.expect_subscriptable(&object);
+                self.check_expr(value)
+            }
+            Expr::Comma(left, _, right) => {
+                self.check_expr(left);
+                self.check_expr(right)
+            }
+            Expr::Conditional(condition, _, then_branch, else_branch) => {
+                self.check_expr(condition);
+                let then_branch = self.check_expr(then_branch);
+                let else_branch = self.check_expr(else_branch);
+                if then_branch == else_branch {
+                    then_branch
+                } else {
+                    Type::Any
+                }
+            }
+            Expr::CompoundAssign(target, operator, value) => {
+                let current = self.check_expr(target);
+                let value = self.check_expr(value);
+                let result = self.binary(operator, current.clone(), value);
+                if !self.is_assignable(&current, &result) {
+                    self.error(format!(
+                        "Cannot assign {} to a target of type {}",
+                        result, current
+                    ));
+                }
+                result
+            }
+            Expr::Increment(target, ..) => {
+                let current = self.check_expr(target);
+                self.expect_number(&current);
+                Type::Number
+            }
+        }
+    }
+
+    // Also used for compound assignments, `+=` behaves like `+`
+    fn binary(&mut self, operator: &Token, left: Type, right: Type) -> Type {
+        use frontend::token::TokenType::*;
+        match operator.token_type {
+            Plus | PlusEqual => match (left, right) {
+                (Type::String, Type::String) => Type::String,
+                (Type::Number, Type::Number) => Type::Number,
+                (Type::Any, Type::String) | (Type::String, Type::Any) => Type::String,
+                (Type::Any, Type::Number) | (Type::Number, Type::Any) => Type::Number,
+                (Type::Any, Type::Any) => Type::Any,
+                (left, right) => {
+                    self.error(format!("Cannot add {} and {}", left, right));
+                    Type::Any
+                }
+            },
+            Greater | GreaterEqual | Less | LessEqual | EqualEqual | BangEqual => Type::Bool,
+            _ => {
+                self.expect_number(&left);
+                self.expect_number(&right);
+                Type::Number
+            }
+        }
+    }
+
+    fn call(&mut self, callee: Type, args: Vec<Type>) -> Type {
+        let (params, return_type) = match callee {
+            Type::Any => return Type::Any,
+            Type::Function(params, return_type) => (params, *return_type),
+            Type::Class(name) => {
+                let params = match self.find_method(&name, "init") {
+                    Some(Type::Function(params, _)) => params,
+                    _ => Some(Vec::new()),
+                };
+                (params, Type::Instance(name))
+            }
+            other => {
+                self.error(format!(
+                    "Can only call functions and classes, got {}",
+                    other
+                ));
+                return Type::Any;
+            }
+        };
+        if let Some(params) = params {
+            if params.len() != args.len() {
+                self.error(format!(
+                    "Expected {} arguments but got {}",
+                    params.len(),
+                    args.len()
+                ));
+            }
+            for (i, (param, arg)) in params.iter().zip(args.iter()).enumerate() {
+                if !self.is_assignable(param, arg) {
+                    self.error(format!(
+                        "Expected argument {} to be {} but got {}",
+                        i + 1,
+                        param,
+                        arg
+                    ));
+                }
+            }
+        }
+        return_type
+    }
+
+    fn expect_number(&mut self, operand: &Type) {
+        if !matches!(operand, Type::Number | Type::Any) {
+            self.error(format!("Expected a Number operand but got {}", operand));
+        }
+    }
+
+    fn expect_subscriptable(&mut self, object: &Type) {
+        if !matches!(object, Type::List | Type::Map | Type::String | Type::Any) {
+            self.error(format!("Cannot subscript {}", object));
+        }
+    }
+
+    fn is_assignable(&self, expected: &Type, actual: &Type) -> bool {
+        match (expected, actual) {
+            (Type::Any, _) | (_, Type::Any) => true,
+            (Type::Instance(expected), Type::Instance(actual)) => {
+                self.is_subclass(actual, expected)
+            }
+            // A bare `Function` annotation accepts any function
+            (Type::Function(None, _), Type::Function(..)) => true,
+            (expected, actual) => expected == actual,
+        }
+    }
+
+    fn is_subclass(&self, class: &str, other: &str) -> bool {
+        class == other
+            || self
+                .classes
+                .get(class)
+                .and_then(|info| info.super_class.as_ref())
+                .is_some_and(|super_class| self.is_subclass(super_class, other))
+    }
+
+    // Looks up a method in a class, falling back to its super classes
+    fn find_method(&self, class: &str, name: &str) -> Option<Type> {
+        let info = self.classes.get(class)?;
+        match info.methods.get(name) {
+            Some(method) => Some(method.clone()),
+            None => self.find_method(info.super_class.as_ref()?, name),
+        }
+    }
+
+    fn signature_type(&mut self, signature: &Signature) -> Type {
+        let params = signature
+            .params
+            .iter()
+            .map(|param| self.annotation_type(param))
+            .collect();
+        let return_type = self.annotation_type(&signature.return_type);
+        Type::Function(Some(params), Box::new(return_type))
+    }
+
+    fn annotation_type(&mut self, annotation: &Option<Token>) -> Type {
+        let annotation = match annotation {
+            Some(annotation) => annotation,
+            None => return Type::Any,
+        };
+        if annotation.token_type == TokenType::Nil {
+            return Type::Nil;
+        }
+        match lexeme(annotation).as_str() {
+            "Any" => Type::Any,
+            "Bool" => Type::Bool,
+            "Number" => Type::Number,
+            "String" => Type::String,
+            "List" => Type::List,
+            "Map" => Type::Map,
+            "Function" => Type::Function(None, Box::new(Type::Any)),
+            name if self.classes.contains_key(name) => Type::Instance(name.to_string()),
+            name => {
+                self.line = annotation.line;
+                self.error(format!("Unknown type '{}'", name));
+                Type::Any
+            }
+        }
+    }
+
+    fn scoped(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(HashMap::new());
+        f(self);
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &Token, value_type: Type) {
+        self.define(&lexeme(name), value_type);
+    }
+
+    fn define(&mut self, name: &str, value_type: Type) {
+        self.bind(name, value_type, false);
+    }
+
+    fn bind(&mut self, name: &str, value_type: Type, inferred: bool) {
+        if let Some(scope) = self.scopes.last_mut() {
+            let binding = Binding {
+                value_type,
+                inferred,
+            };
+            scope.insert(name.to_string(), binding);
+        }
+    }
+
+    fn binding_mut(&mut self, name: &str) -> Option<&mut Binding> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+    }
+
+    // Names the checker has not seen, like natives and imports, are `Any`
+    fn lookup(&self, name: &str) -> Type {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .map(|binding| binding.value_type.clone())
+            .unwrap_or(Type::Any)
+    }
+
+    fn error(&mut self, message: String) {
+        self.errors.push(TypeError {
+            message,
+            line: self.line,
+        });
+    }
+}
+
+fn lexeme(token: &Token) -> String {
+    token.lexeme.clone().unwrap_or_default()
+}
@@ -13,7 +13,7 @@ impl Callable for Clock {
         &self,
         _interpreter: &mut dyn frontend::runnable::Runnable,
         _args: Vec<frontend::literal::Literal>,
-    ) -> frontend::scanner::ScannerResult<frontend::literal::Literal> {
+    ) -> frontend::runnable::InterpreterResult<frontend::literal::Literal> {
         Ok(Literal::Number(
             SystemTime::now()
                 .duration_since(UNIX_EPOCH)
@@ -0,0 +1,61 @@
+use frontend::class::{Class, Methods};
+use frontend::{callable::Callable, instance::Instance, literal::Literal};
+use std::rc::Rc;
+
+// Native initializer of the built-in `Error` class, `Error(message)`
+#[derive(Debug, Clone)]
+pub struct ErrorInit {
+    instance: Option<Instance>,
+}
+
+impl Callable for ErrorInit {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn call(
+        &self,
+        _interpreter: &mut dyn frontend::runnable::Runnable,
+        args: Vec<frontend::literal::Literal>,
+    ) -> frontend::runnable::InterpreterResult<frontend::literal::Literal> {
+        if let Some(instance) = self.instance.as_ref() {
+            let mut fields = instance.fields.borrow_mut();
+            fields.insert(
+                "message".to_string(),
+                args.into_iter().next().unwrap_or(Literal::Nil),
+            );
+            fields.insert("line".to_string(), Literal::Nil);
+        }
+        Ok(Literal::Nil)
+    }
+
+    fn box_clone(&self) -> Box<dyn Callable> {
+        Box::new(self.clone())
+    }
+
+    fn bind(&self, instance: Instance) -> Box<dyn Callable> {
+        Box::new(ErrorInit {
+            instance: Some(instance),
+        })
+    }
+}
+
+pub fn error_class() -> Class {
+    let mut methods = Methods::default();
+    methods.instance.insert(
+        "init".to_string(),
+        Literal::Callable(Box::new(ErrorInit { instance: None })),
+    );
+    Class::new("Error".to_string(), None, methods)
+}
+
+// Wraps a runtime error in an `Error` instance so Lox code can catch it
+pub fn error_instance(class: &Rc<Class>, message: String, line: usize) -> Literal {
+    let instance = Instance::new(Rc::clone(class));
+    {
+        let mut fields = instance.fields.borrow_mut();
+        fields.insert("message".to_string(), Literal::String(message));
+        fields.insert("line".to_string(), Literal::Integer(line as i64));
+    }
+    Literal::Instance(instance)
+}
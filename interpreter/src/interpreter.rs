@@ -1,9 +1,14 @@
-use frontend::ast::{Expr, Stmt};
+use crate::exception;
+use crate::module_loader::{self, LoadedSource, ModuleLoader};
+use frontend::ast::{Expr, MethodKind, NodeId, Pattern, Stmt};
 use frontend::callable::Callable;
-use frontend::class::Class;
+use frontend::class::{Class, Methods};
 use frontend::environment::Environment;
 use frontend::function::Function;
+use frontend::list::{self, List};
 use frontend::literal::{Literal, TryFromWrapper};
+use frontend::map;
+use frontend::module::Module;
 use frontend::runnable::{EarlyReturn, Runnable};
 use frontend::token::{Token, TokenType};
 use std::cell::RefCell;
@@ -15,10 +20,46 @@ use utils::errors::InterpreterError;
 
 pub type InterpreterResult<T> = Result<T, EarlyReturn>;
 
+// Drives a for-in loop over a built-in collection or a user defined iterator
+enum ForInIterator {
+    // Lists are indexed live, so elements pushed inside the loop are visited too
+    List(List, usize),
+    Values(std::vec::IntoIter<Literal>),
+    // Bound `next` method of an iterator instance, which returns nil when exhausted
+    Instance(Box<dyn Callable>),
+}
+
+impl ForInIterator {
+    fn next(&mut self, interpreter: &mut Interpreter) -> InterpreterResult<Option<Literal>> {
+        match self {
+            ForInIterator::List(l, i) => {
+                let value = l.borrow().get(*i).cloned();
+                *i += 1;
+                Ok(value)
+            }
+            ForInIterator::Values(values) => Ok(values.next()),
+            ForInIterator::Instance(next) => match next.call(interpreter, Vec::new())? {
+                Literal::Nil => Ok(None),
+                value => Ok(Some(value)),
+            },
+        }
+    }
+}
+
 pub struct Interpreter {
     pub environment: Rc<RefCell<Environment>>,
     pub globals: Rc<RefCell<Environment>>,
-    locals: HashMap<Expr, usize>,
+    // Scope distance and slot of every variable reference the resolver found a local for
+    locals: HashMap<NodeId, (usize, usize)>,
+    // Built-in class that caught runtime errors are converted into
+    error_class: Rc<Class>,
+    // Line of the expression being evaluated, reported on caught runtime errors
+    line: usize,
+    // Cross-checks resolved variables against a lookup by name
+    pub debug: bool,
+    // Disagreements found in debug mode
+    pub diagnostics: Vec<String>,
+    pub modules: ModuleLoader,
 }
 
 impl Default for Interpreter {
@@ -28,11 +69,12 @@ impl Default for Interpreter {
 }
 
 impl Runnable for Interpreter {
-    fn block(&mut self, body: Vec<Stmt>, e: Rc<RefCell<Environment>>) -> InterpreterResult<()> {
+    fn block(&mut self, body: &[Stmt], e: Rc<RefCell<Environment>>) -> InterpreterResult<()> {
         let previous = mem::replace(&mut self.environment, e);
-        self.interpret(body)?;
+        // Restore the enclosing scope even when unwinding from a return or error
+        let res = self.interpret(body);
         self.environment = previous;
-        Ok(())
+        res
     }
 }
 
@@ -41,163 +83,238 @@ impl Interpreter {
         let globals = e.into_cell();
         let environment = Rc::clone(&globals);
         let locals = HashMap::new();
+        let error_class = Rc::new(exception::error_class());
+        globals
+            .borrow_mut()
+            .define("Error".to_string(), Literal::Class(Rc::clone(&error_class)));
         Interpreter {
             globals,
             environment,
             locals,
+            error_class,
+            line: 0,
+            debug: false,
+            diagnostics: Vec::new(),
+            modules: ModuleLoader::default(),
         }
     }
 
-    pub fn interpret(&mut self, stmts: Vec<Stmt>) -> InterpreterResult<()> {
+    pub fn interpret(&mut self, stmts: &[Stmt]) -> InterpreterResult<()> {
         for stmt in stmts {
-            match stmt {
-                Stmt::Expr(e) => {
-                    self.evaluate(&e)?;
-                }
-                Stmt::Print(e) => {
-                    self.print_statement(e)?;
-                }
-                Stmt::Var(name, init) => self.var_statement(name, init)?,
-                Stmt::Block(stmts) => self.block(
-                    stmts,
-                    Environment::new(Some(Rc::clone(&self.environment))).into_cell(),
-                )?,
-                Stmt::If(condition, consequent, alternative) => {
-                    self.if_statement(condition, *consequent, alternative.map(|alt| *alt))?
-                }
-                Stmt::While(condition, body) => self.while_statement(condition, *body)?,
-                Stmt::Function(name, params, body) => self.function(name, params, body)?,
-                Stmt::Return(_return_keyword, return_value) => {
-                    self.return_statement(return_value)?
-                }
-                Stmt::Class(name, super_class, methods) => {
-                    self.class_stmt(name, super_class, methods)?
-                }
+            self.execute(stmt)?;
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, stmt: &Stmt) -> InterpreterResult<()> {
+        match stmt {
+            Stmt::Expr(e) => {
+                self.evaluate(e)?;
+            }
+            Stmt::Print(e) => self.print_statement(e)?,
+            Stmt::Var(name, _, init) => self.var_statement(name, init)?,
+            Stmt::Block(stmts) => self.block(
+                stmts,
+                Environment::new(Some(Rc::clone(&self.environment))).into_cell(),
+            )?,
+            Stmt::If(condition, consequent, alternative) => {
+                self.if_statement(condition, consequent, alternative)?
+            }
+            Stmt::While(condition, body, increment) => {
+                self.while_statement(condition, body, increment)?
+            }
+            Stmt::ForIn(name, iterable, body) => self.for_in_statement(name, iterable, body)?,
+            Stmt::Function(name, params, body, _) => self.function(name, params, body)?,
+            Stmt::Return(_return_keyword, return_value) => self.return_statement(return_value)?,
+            Stmt::Class(name, super_class, methods) => {
+                self.class_stmt(name, super_class, methods)?
+            }
+            Stmt::Throw(_keyword, value) => return Err(EarlyReturn::Throw(self.evaluate(value)?)),
+            Stmt::Try(body, catch, finally) => self.try_statement(body, catch, finally)?,
+            Stmt::Match(_keyword, subject, cases, default) => {
+                self.match_statement(subject, cases, default)?
             }
+            Stmt::Import(_keyword, path, name) => self.import_statement(path, name)?,
+            Stmt::Export(declaration) => self.execute(declaration)?,
+            Stmt::Break(_) => return Err(EarlyReturn::Break),
+            Stmt::Continue(_) => return Err(EarlyReturn::Continue),
         }
         Ok(())
     }
 
     fn evaluate(&mut self, expr: &Expr) -> InterpreterResult<Literal> {
+        if let Some(line) = expr.line() {
+            self.line = line;
+        }
         match *expr {
             Expr::Literal(ref l) => Ok(l.clone()),
             Expr::Grouping(ref e) => self.evaluate(e),
+            Expr::Stringify(ref e) => Ok(Literal::String(self.evaluate(e)?.to_string())),
+            Expr::Comma(ref left, _, ref right) => {
+                self.evaluate(left)?;
+                self.evaluate(right)
+            }
+            Expr::CompoundAssign(ref target, ref operator, ref value) => {
+                self.compound_assign_expr(target, operator, value)
+            }
+            Expr::Increment(ref target, ref operator, prefix) => {
+                self.increment_expr(target, operator, prefix)
+            }
+            Expr::Conditional(ref condition, _, ref then_branch, ref else_branch) => {
+                if bool::from(self.evaluate(condition)?) {
+                    self.evaluate(then_branch)
+                } else {
+                    self.evaluate(else_branch)
+                }
+            }
             Expr::Unary(ref operator, ref right) => self.unary_expr(operator, right),
             Expr::Binary(ref left, ref operator, ref right) => {
                 self.binary_expr(left, operator, right)
             }
-            Expr::Variable(ref name) => self.var_expression(expr, name),
-            Expr::Assign(ref name, ref init) => self.assignment_expression(expr, name, init),
+            Expr::Variable(ref name, id) => self.lookup_variable(id, name),
+            Expr::Assign(ref name, ref init, id) => self.assignment_expression(id, name, init),
             Expr::Logical(ref left, ref operator, ref right) => {
                 self.logical_expression(left, operator, right)
             }
             Expr::Call(ref callee, ref _paren, ref args) => self.call_expression(callee, args),
             Expr::Get(ref obj, ref name) => self.get_expr(obj, name),
             Expr::Set(ref obj, ref name, ref new_value) => self.set_expr(obj, name, new_value),
-            Expr::This(ref name) => {
-                self.lookup_variable(expr, name.lexeme.as_ref().unwrap().as_str())
+            Expr::This(ref name, id) => self.lookup_variable(id, name),
+            Expr::Super(ref keyword, ref method, id) => self.super_expr(keyword, method, id),
+            Expr::Lambda(_, ref params, ref body, _) => {
+                let this = self.environment.borrow().get_local("this");
+                Ok(Literal::Callable(Box::new(Function::lambda(
+                    params.as_slice().into(),
+                    Rc::clone(body),
+                    Rc::clone(&self.environment),
+                    this.unwrap_or(Literal::Nil),
+                ))))
+            }
+            Expr::List(ref elements) => self.list_expr(elements),
+            Expr::Map(ref entries) => self.map_expr(entries),
+            Expr::Subscript(ref obj, _, ref index) => self.subscript_expr(obj, index),
+            Expr::SetSubscript(ref obj, _, ref index, ref new_value) => {
+                self.set_subscript_expr(obj, index, new_value)
             }
-            Expr::Super(..) => self.super_expr(expr),
         }
     }
 
-    pub fn resolve(&mut self, expr: Expr, depth: usize) {
-        self.locals.insert(expr, depth);
+    pub fn resolve(&mut self, locals: HashMap<NodeId, (usize, usize)>) {
+        self.locals.extend(locals);
     }
 
     fn class_stmt(
         &mut self,
-        name: Token,
-        super_class: Option<Expr>,
-        methods: Vec<Stmt>,
+        name: &Token,
+        super_class: &Option<Expr>,
+        methods: &[(MethodKind, Stmt)],
     ) -> InterpreterResult<()> {
-        if let Some(lex) = name.lexeme {
+        if let Some(lex) = name.lexeme.clone() {
             let mut super_class_eval = None;
             if let Some(super_class) = super_class.as_ref() {
                 let super_class_eval_unmatched = self.evaluate(super_class).ok();
                 match super_class_eval_unmatched {
-                    Some(Literal::Class(c)) => super_class_eval = Some(Box::new(c)),
+                    Some(Literal::Class(c)) => super_class_eval = Some(c),
                     None => {}
                     _ => return Err(EarlyReturn::Error(InterpreterError::InvalidAstType)),
                 }
             }
-            self.environment
-                .borrow_mut()
-                .define(lex.clone(), Literal::Nil);
-
-            let mut prev_env = None;
+            // Static methods have no `super`, so they close over the enclosing scope
+            let enclosing = Rc::clone(&self.environment);
+            let mut method_env = Rc::clone(&enclosing);
             if let Some(s) = super_class_eval.as_ref() {
-                let enclosing = Rc::clone(&self.environment);
-                prev_env = Some(mem::replace(
-                    &mut self.environment,
-                    Environment::new(Some(enclosing)).into_cell(),
-                ));
-                self.environment
-                    .borrow_mut()
-                    .define("super".to_string(), Literal::Class(*s.clone())); // HACK cloning is ok since classes dont hold state
+                let mut super_env = Environment::new(Some(Rc::clone(&enclosing)));
+                super_env.define("super".to_string(), Literal::Class(Rc::clone(s)));
+                method_env = super_env.into_cell();
             }
-            let mut name_to_methods = HashMap::new();
-
-            for m in methods {
-                if let Stmt::Function(name, params, body) = m {
-                    if let Some(name) = name.lexeme {
+            let mut name_to_methods = Methods::default();
+
+            for (kind, m) in methods {
+                if let Stmt::Function(name, params, body, _) = m {
+                    if let Some(name) = name.lexeme.clone() {
+                        let is_init = *kind == MethodKind::Method && name.as_str() == "init";
+                        let closure = match kind {
+                            MethodKind::Static => &enclosing,
+                            _ => &method_env,
+                        };
                         let func = Function::new(
-                            params,
-                            body,
-                            Rc::clone(&self.environment),
-                            name.as_str() == "init",
+                            params.as_slice().into(),
+                            Rc::clone(body),
+                            Rc::clone(closure),
+                            is_init,
                         );
-                        name_to_methods.insert(name, Literal::Callable(Box::new(func)));
+                        let table = match kind {
+                            MethodKind::Method => &mut name_to_methods.instance,
+                            MethodKind::Static => &mut name_to_methods.statics,
+                            MethodKind::Getter => &mut name_to_methods.getters,
+                            MethodKind::Setter => &mut name_to_methods.setters,
+                        };
+                        table.insert(name, Literal::Callable(Box::new(func)));
                     }
                 }
             }
 
+            // Methods only look the class up when called, so it can be defined last
             let class = Class::new(lex.clone(), super_class_eval, name_to_methods);
-            if super_class.is_some() {
-                self.environment = prev_env.unwrap();
-            }
             self.environment
                 .borrow_mut()
-                .assign(lex, Literal::Class(class))
-                .map_err(EarlyReturn::Error)?;
+                .define(lex, Literal::Class(Rc::new(class)));
         }
         Ok(())
     }
 
-    fn super_expr(&self, expr: &Expr) -> InterpreterResult<Literal> {
-        let distance = *self.locals.get(expr).unwrap();
-        let super_class = self
-            .environment
-            .borrow_mut()
-            .get_at(distance, "super")
-            .unwrap();
+    fn super_expr(
+        &mut self,
+        keyword: &Token,
+        method: &Token,
+        id: NodeId,
+    ) -> InterpreterResult<Literal> {
+        // `super` and `this` are each the only variable of their scope
+        let super_class = self.lookup_variable(id, keyword)?;
+        let (distance, _) = self.locals[&id];
+        let this = Token {
+            lexeme: Some("this".to_string()),
+            ..keyword.clone()
+        };
         let instance = self
             .environment
-            .borrow_mut()
-            .get_at(distance - 1, "this")
-            .unwrap();
-        if let Literal::Class(s) = super_class {
-            if let Expr::Super(_keyword, method) = expr {
-                let method_name = method.lexeme.as_ref().unwrap();
-                let method = s.get_method(method_name).unwrap();
-                if let Literal::Callable(m) = method {
-                    if let Literal::Instance(i) = instance {
-                        m.bind(i);
-                        return Ok(Literal::Callable(m));
-                    }
+            .borrow()
+            .get_at(distance - 1, 0, &this)
+            .map_err(EarlyReturn::Error)?;
+        let method_name = method.lexeme.clone().unwrap_or_default();
+        match (super_class, instance) {
+            (Literal::Class(class), Literal::Instance(instance)) => {
+                match class.get_method(&method_name) {
+                    Some(Literal::Callable(method)) => Ok(Literal::Callable(method.bind(instance))),
+                    _ => Err(EarlyReturn::Error(InterpreterError::UndefinedProperty(
+                        method_name,
+                    ))),
                 }
             }
+            _ => Err(EarlyReturn::Error(InterpreterError::InvalidAstType)),
         }
-        unreachable!();
     }
 
     fn get_expr(&mut self, obj: &Expr, name: &Token) -> InterpreterResult<Literal> {
         let obj = self.evaluate(obj)?;
-        if let Literal::Instance(instance) = obj {
-            Ok(instance.get(name.clone()))
-        } else {
-            Err(EarlyReturn::Error(InterpreterError::InvalidAstType))
-        }
+        self.get_property(obj, name)
+    }
+
+    fn get_property(&mut self, obj: Literal, name: &Token) -> InterpreterResult<Literal> {
+        let method_name = name.lexeme.clone().unwrap_or_default();
+        let method = match obj {
+            Literal::Instance(instance) => return instance.get(name.clone(), self),
+            Literal::Class(class) => class
+                .get_field(&method_name)
+                .or_else(|| class.get_static(&method_name)),
+            Literal::List(l) => list::get_method(&l, &method_name),
+            Literal::Map(m) => map::get_method(&m, &method_name),
+            Literal::Module(m) => m.get(&method_name),
+            _ => return Err(EarlyReturn::Error(InterpreterError::InvalidAstType)),
+        };
+        method.ok_or(EarlyReturn::Error(InterpreterError::UndefinedProperty(
+            method_name,
+        )))
     }
 
     fn set_expr(
@@ -208,12 +325,165 @@ impl Interpreter {
     ) -> InterpreterResult<Literal> {
         let obj = self.evaluate(obj)?;
         let new_value = self.evaluate(new_value)?;
-        if let Literal::Instance(mut instance) = obj {
-            instance.set(name.clone(), new_value.clone());
-            Ok(new_value)
-        } else {
-            Err(EarlyReturn::Error(InterpreterError::InvalidAstType))
+        self.set_property(obj, name, new_value)
+    }
+
+    fn set_property(
+        &mut self,
+        obj: Literal,
+        name: &Token,
+        new_value: Literal,
+    ) -> InterpreterResult<Literal> {
+        match obj {
+            Literal::Instance(mut instance) => {
+                instance.set(name.clone(), new_value.clone(), self)?
+            }
+            Literal::Class(class) => {
+                class.set_field(name.lexeme.clone().unwrap_or_default(), new_value.clone())
+            }
+            _ => return Err(EarlyReturn::Error(InterpreterError::InvalidAstType)),
+        }
+        Ok(new_value)
+    }
+
+    fn compound_assign_expr(
+        &mut self,
+        target: &Expr,
+        operator: &Token,
+        value: &Expr,
+    ) -> InterpreterResult<Literal> {
+        use frontend::token::TokenType::*;
+        let operator = match operator.token_type {
+            PlusEqual => Plus,
+            MinusEqual => Minus,
+            StarEqual => Star,
+            SlashEqual => Slash,
+            PercentEqual => Percent,
+            _ => return Err(EarlyReturn::Error(InterpreterError::InvalidAstType)),
+        };
+        let (_, new_value) = self.update_target(target, |interpreter, old| {
+            let value = interpreter.evaluate(value)?;
+            Self::binary_op(&operator, old, value)
+        })?;
+        Ok(new_value)
+    }
+
+    fn increment_expr(
+        &mut self,
+        target: &Expr,
+        operator: &Token,
+        prefix: bool,
+    ) -> InterpreterResult<Literal> {
+        let operator = match operator.token_type {
+            TokenType::PlusPlus => TokenType::Plus,
+            _ => TokenType::Minus,
+        };
+        let (old_value, new_value) = self.update_target(target, |_, old| {
+            Self::arithmetic(&operator, old, Literal::Integer(1))
+        })?;
+        Ok(if prefix { new_value } else { old_value })
+    }
+
+    // Reads the target, applies `update` and writes the result back, evaluating the
+    // object and index of the target only once. Returns the old and new values.
+    fn update_target(
+        &mut self,
+        target: &Expr,
+        update: impl FnOnce(&mut Self, Literal) -> InterpreterResult<Literal>,
+    ) -> InterpreterResult<(Literal, Literal)> {
+        match target {
+            Expr::Variable(name, id) => {
+                let old_value = self.lookup_variable(*id, name)?;
+                let new_value = update(self, old_value.clone())?;
+                self.assign_variable(*id, name, new_value.clone())?;
+                Ok((old_value, new_value))
+            }
+            Expr::Get(obj, name) => {
+                let obj = self.evaluate(obj)?;
+                let old_value = self.get_property(obj.clone(), name)?;
+                let new_value = update(self, old_value.clone())?;
+                self.set_property(obj, name, new_value.clone())?;
+                Ok((old_value, new_value))
+            }
+            Expr::Subscript(obj, _, index) => {
+                let obj = self.evaluate(obj)?;
+                let index = self.evaluate(index)?;
+                let old_value = self.get_subscript(obj.clone(), index.clone())?;
+                let new_value = update(self, old_value.clone())?;
+                self.set_subscript(obj, index, new_value.clone())?;
+                Ok((old_value, new_value))
+            }
+            _ => Err(EarlyReturn::Error(InterpreterError::InvalidAstType)),
+        }
+    }
+
+    fn list_expr(&mut self, elements: &[Expr]) -> InterpreterResult<Literal> {
+        let mut values = Vec::new();
+        for e in elements {
+            values.push(self.evaluate(e)?);
+        }
+        Ok(Literal::List(list::new_list(values)))
+    }
+
+    fn map_expr(&mut self, entries: &[(Expr, Expr)]) -> InterpreterResult<Literal> {
+        let mut values = Vec::new();
+        for (key, value) in entries {
+            values.push((self.evaluate(key)?, self.evaluate(value)?));
         }
+        Ok(Literal::Map(map::new_map(values)?))
+    }
+
+    fn subscript_expr(&mut self, obj: &Expr, index: &Expr) -> InterpreterResult<Literal> {
+        let obj = self.evaluate(obj)?;
+        let index = self.evaluate(index)?;
+        self.get_subscript(obj, index)
+    }
+
+    fn get_subscript(&self, obj: Literal, index: Literal) -> InterpreterResult<Literal> {
+        match obj {
+            Literal::List(l) => {
+                let l = l.borrow();
+                let i = list::to_index(index, l.len(), false)?;
+                Ok(l[i].clone())
+            }
+            Literal::Map(m) => m.borrow().get(&index).cloned().ok_or(EarlyReturn::Error(
+                InterpreterError::UndefinedKey(index.to_string()),
+            )),
+            _ => Err(EarlyReturn::Error(InterpreterError::InvalidAstType)),
+        }
+    }
+
+    fn set_subscript_expr(
+        &mut self,
+        obj: &Expr,
+        index: &Expr,
+        new_value: &Expr,
+    ) -> InterpreterResult<Literal> {
+        let obj = self.evaluate(obj)?;
+        let index = self.evaluate(index)?;
+        let new_value = self.evaluate(new_value)?;
+        self.set_subscript(obj, index, new_value)
+    }
+
+    fn set_subscript(
+        &self,
+        obj: Literal,
+        index: Literal,
+        new_value: Literal,
+    ) -> InterpreterResult<Literal> {
+        match obj {
+            Literal::List(l) => {
+                let mut l = l.borrow_mut();
+                let i = list::to_index(index, l.len(), false)?;
+                l[i] = new_value.clone();
+            }
+            Literal::Map(m) => {
+                let key = map::check_key(index)?;
+                m.borrow_mut().insert(key, new_value.clone());
+            }
+            _ => return Err(EarlyReturn::Error(InterpreterError::InvalidAstType)),
+        }
+        Ok(new_value)
     }
 
     fn call_expression(&mut self, callee: &Expr, args: &[Expr]) -> InterpreterResult<Literal> {
@@ -228,24 +498,26 @@ impl Interpreter {
                 if arg_literals.len() != function.arity() {
                     return Err(EarlyReturn::Error(InterpreterError::MismatchFunctionArity));
                 }
-                function
-                    .call(self, arg_literals)
-                    .map_err(|_| EarlyReturn::Error(InterpreterError::InvalidAstType))
+                function.call(self, arg_literals)
             }
             Literal::Class(c) => {
                 if arg_literals.len() != c.arity() {
                     return Err(EarlyReturn::Error(InterpreterError::MismatchFunctionArity));
                 }
                 c.call(self, arg_literals)
-                    .map_err(|_| EarlyReturn::Error(InterpreterError::InvalidAstType))
             }
             _ => Err(EarlyReturn::Error(InterpreterError::InvalidAstType)),
         }
     }
 
-    fn function(&self, name: Token, params: Vec<Token>, body: Vec<Stmt>) -> InterpreterResult<()> {
-        let function = Function::new(params, body, Rc::clone(&self.environment), false);
-        if let Some(name) = name.lexeme {
+    fn function(&self, name: &Token, params: &[Token], body: &Rc<[Stmt]>) -> InterpreterResult<()> {
+        let function = Function::new(
+            params.into(),
+            Rc::clone(body),
+            Rc::clone(&self.environment),
+            false,
+        );
+        if let Some(name) = name.lexeme.clone() {
             self.environment
                 .borrow_mut()
                 .define(name, Literal::Callable(Box::new(function)));
@@ -253,31 +525,211 @@ impl Interpreter {
         Ok(())
     }
 
-    fn return_statement(&mut self, return_value: Option<Expr>) -> InterpreterResult<()> {
+    fn return_statement(&mut self, return_value: &Option<Expr>) -> InterpreterResult<()> {
         if let Some(return_value) = return_value {
-            let value = self.evaluate(&return_value)?;
+            let value = self.evaluate(return_value)?;
             return Err(EarlyReturn::Return(value));
         }
         Err(EarlyReturn::Return(Literal::Nil))
     }
 
-    fn while_statement(&mut self, condition: Expr, body: Stmt) -> InterpreterResult<()> {
-        while bool::from(self.evaluate(&condition)?) {
-            self.interpret(vec![body.clone()])?; // TODO expensive clone, how to use ref for this?
+    fn while_statement(
+        &mut self,
+        condition: &Expr,
+        body: &Stmt,
+        increment: &Option<Expr>,
+    ) -> InterpreterResult<()> {
+        while bool::from(self.evaluate(condition)?) {
+            match self.execute(body) {
+                Ok(()) | Err(EarlyReturn::Continue) => {}
+                Err(EarlyReturn::Break) => break,
+                Err(e) => return Err(e),
+            }
+            if let Some(increment) = increment.as_ref() {
+                self.evaluate(increment)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn import_statement(&mut self, path: &str, name: &Token) -> InterpreterResult<()> {
+        let path = self
+            .modules
+            .resolve_path(path)
+            .map_err(EarlyReturn::Error)?;
+        let module = match self.modules.cached(&path) {
+            Some(module) => module,
+            None => self.load_module(path)?,
+        };
+        self.environment.borrow_mut().define(
+            name.lexeme.clone().unwrap_or_default(),
+            Literal::Module(module),
+        );
+        Ok(())
+    }
+
+    fn load_module(&mut self, path: std::path::PathBuf) -> InterpreterResult<Module> {
+        let LoadedSource { path, ast, locals } =
+            self.modules.load(path).map_err(EarlyReturn::Error)?;
+        self.resolve(locals);
+        let exports = module_loader::exported_names(&ast);
+
+        // Modules get their own top-level scope, on top of the globals for natives
+        let environment = Environment::top_level(Rc::clone(&self.globals)).into_cell();
+        let res = self.block(&ast, Rc::clone(&environment));
+        let module = res.as_ref().ok().map(|_| {
+            Module::new(
+                path.display().to_string(),
+                environment,
+                exports.into_iter().collect(),
+            )
+        });
+        self.modules.finish(path, module.clone());
+        res?;
+        Ok(module.unwrap())
+    }
+
+    fn try_statement(
+        &mut self,
+        body: &[Stmt],
+        catch: &Option<(Token, Vec<Stmt>)>,
+        finally: &Option<Vec<Stmt>>,
+    ) -> InterpreterResult<()> {
+        let mut res = self.block(
+            body,
+            Environment::new(Some(Rc::clone(&self.environment))).into_cell(),
+        );
+
+        if let Some((name, handler)) = catch {
+            let caught = match res {
+                Err(EarlyReturn::Throw(ref value)) => Some(value.clone()),
+                Err(EarlyReturn::Error(ref e)) => Some(exception::error_instance(
+                    &self.error_class,
+                    e.to_string(),
+                    self.line,
+                )),
+                _ => None,
+            };
+            if let Some(caught) = caught {
+                let mut environment = Environment::new(Some(Rc::clone(&self.environment)));
+                environment.define(name.lexeme.clone().unwrap_or_default(), caught);
+                res = self.block(handler, environment.into_cell());
+            }
+        }
+
+        // The finally block always runs, and its own early exit takes precedence
+        if let Some(finally) = finally {
+            self.block(
+                finally,
+                Environment::new(Some(Rc::clone(&self.environment))).into_cell(),
+            )?;
+        }
+        res
+    }
+
+    fn match_statement(
+        &mut self,
+        subject: &Expr,
+        cases: &[(Vec<Pattern>, Stmt)],
+        default: &Option<Box<Stmt>>,
+    ) -> InterpreterResult<()> {
+        let subject = self.evaluate(subject)?;
+        for (patterns, body) in cases {
+            for pattern in patterns.iter() {
+                if self.matches(&subject, pattern)? {
+                    return self.execute(body);
+                }
+            }
+        }
+        match default {
+            Some(default) => self.execute(default),
+            None => Ok(()),
+        }
+    }
+
+    fn matches(&mut self, subject: &Literal, pattern: &Pattern) -> InterpreterResult<bool> {
+        match pattern {
+            Pattern::Literal(_, literal) => Ok(subject == literal),
+            Pattern::Class(class) => match self.evaluate(class)? {
+                Literal::Class(class) => Ok(match subject {
+                    Literal::Instance(instance) => instance.is_instance_of(&class),
+                    _ => false,
+                }),
+                other => Err(EarlyReturn::Error(InterpreterError::NotAClass(
+                    other.to_string(),
+                ))),
+            },
+        }
+    }
+
+    fn for_in_statement(
+        &mut self,
+        name: &Token,
+        iterable: &Expr,
+        body: &Stmt,
+    ) -> InterpreterResult<()> {
+        let iterable = self.evaluate(iterable)?;
+        let mut iterator = self.iterator(iterable)?;
+        let name = name.lexeme.clone().unwrap_or_default();
+        while let Some(value) = iterator.next(self)? {
+            // Each iteration gets a fresh scope so closures capture that iteration's value
+            let mut environment = Environment::new(Some(Rc::clone(&self.environment)));
+            environment.define(name.clone(), value);
+            match self.block(std::slice::from_ref(body), environment.into_cell()) {
+                Ok(()) | Err(EarlyReturn::Continue) => {}
+                Err(EarlyReturn::Break) => break,
+                Err(e) => return Err(e),
+            }
         }
         Ok(())
     }
 
+    // Instances are iterable through an `iter()` method returning the iterator, or by
+    // being an iterator themselves with a `next()` method
+    fn iterator(&mut self, iterable: Literal) -> InterpreterResult<ForInIterator> {
+        match iterable {
+            Literal::List(l) => Ok(ForInIterator::List(l, 0)),
+            Literal::Map(m) => Ok(ForInIterator::Values(m.borrow().keys().into_iter())),
+            Literal::String(s) => {
+                let chars: Vec<Literal> =
+                    s.chars().map(|c| Literal::String(c.to_string())).collect();
+                Ok(ForInIterator::Values(chars.into_iter()))
+            }
+            Literal::Instance(ref instance) => {
+                if let Some(iter) = instance.get_method("iter") {
+                    return match iter.call(self, Vec::new())? {
+                        Literal::Instance(iterator) => match iterator.get_method("next") {
+                            Some(next) => Ok(ForInIterator::Instance(next)),
+                            None => Err(EarlyReturn::Error(InterpreterError::NotIterable(
+                                iterator.to_string(),
+                            ))),
+                        },
+                        collection => self.iterator(collection),
+                    };
+                }
+                match instance.get_method("next") {
+                    Some(next) => Ok(ForInIterator::Instance(next)),
+                    None => Err(EarlyReturn::Error(InterpreterError::NotIterable(
+                        iterable.to_string(),
+                    ))),
+                }
+            }
+            _ => Err(EarlyReturn::Error(InterpreterError::NotIterable(
+                iterable.to_string(),
+            ))),
+        }
+    }
+
     fn if_statement(
         &mut self,
-        condition: Expr,
-        consequent: Stmt,
-        alternative: Option<Stmt>,
+        condition: &Expr,
+        consequent: &Stmt,
+        alternative: &Option<Box<Stmt>>,
     ) -> InterpreterResult<()> {
-        if bool::from(self.evaluate(&condition)?) {
-            self.interpret(vec![consequent])?;
+        if bool::from(self.evaluate(condition)?) {
+            self.execute(consequent)?;
         } else if let Some(alt) = alternative {
-            self.interpret(vec![alt])?;
+            self.execute(alt)?;
         }
         Ok(())
     }
@@ -301,44 +753,50 @@ impl Interpreter {
 
     fn assignment_expression(
         &mut self,
-        expr: &Expr,
+        id: NodeId,
         name: &Token,
         init: &Expr,
     ) -> InterpreterResult<Literal> {
         let value = self.evaluate(init)?;
+        self.assign_variable(id, name, value)
+    }
 
-        let distance = self.locals.get(expr);
+    fn assign_variable(
+        &mut self,
+        id: NodeId,
+        name: &Token,
+        value: Literal,
+    ) -> InterpreterResult<Literal> {
+        let local = self.locals.get(&id).copied();
+        self.check_resolution(name, local);
 
-        if let Some(name) = &name.lexeme {
-            let name = name.to_string();
-            let assign_result;
-            if let Some(distance) = distance {
-                assign_result =
-                    self.environment
-                        .borrow_mut()
-                        .assign_at(*distance, name, value.clone());
-            } else {
-                assign_result = self.globals.borrow_mut().assign(name, value.clone());
+        let assign_result = match local {
+            Some((distance, slot)) => {
+                self.environment
+                    .borrow_mut()
+                    .assign_at(distance, slot, name, value.clone())
             }
-
-            return assign_result.map(|()| value).map_err(EarlyReturn::Error);
-        }
-        Ok(value)
+            None => {
+                let name = name.lexeme.clone().unwrap_or_default();
+                self.environment.borrow_mut().assign(name, value.clone())
+            }
+        };
+        assign_result.map(|()| value).map_err(EarlyReturn::Error)
     }
 
-    fn print_statement(&mut self, expr: Expr) -> InterpreterResult<()> {
-        let value = self.evaluate(&expr)?;
+    fn print_statement(&mut self, expr: &Expr) -> InterpreterResult<()> {
+        let value = self.evaluate(expr)?;
         println!("{}", value);
         Ok(())
     }
 
-    fn var_statement(&mut self, name: Token, init: Option<Expr>) -> InterpreterResult<()> {
+    fn var_statement(&mut self, name: &Token, init: &Option<Expr>) -> InterpreterResult<()> {
         let mut value = None;
-        if init.is_some() {
-            value = Some(self.evaluate(&init.unwrap())?);
+        if let Some(init) = init {
+            value = Some(self.evaluate(init)?);
         }
 
-        if let Some(name) = name.lexeme {
+        if let Some(name) = name.lexeme.clone() {
             match value {
                 Some(v) => self.environment.borrow_mut().define(name, v),
                 None => self.environment.borrow_mut().define(name, Literal::Nil),
@@ -347,31 +805,71 @@ impl Interpreter {
         Ok(())
     }
 
-    fn var_expression(&mut self, expr: &Expr, name: &Token) -> InterpreterResult<Literal> {
-        let name = name
-            .lexeme
-            .as_ref()
-            .expect("Expected lexeme for variable lookup");
-        self.lookup_variable(expr, name)
+    fn lookup_variable(&mut self, id: NodeId, name: &Token) -> InterpreterResult<Literal> {
+        let local = self.locals.get(&id).copied();
+        self.check_resolution(name, local);
+
+        match local {
+            Some((distance, slot)) => self
+                .environment
+                .borrow()
+                .get_at(distance, slot, name)
+                .map_err(EarlyReturn::Error),
+            None => {
+                let name = name
+                    .lexeme
+                    .as_ref()
+                    .expect("Expected lexeme for variable lookup");
+                self.environment.borrow().get(name).ok_or_else(|| {
+                    EarlyReturn::Error(InterpreterError::UndefinedVariable(name.to_string()))
+                })
+            }
+        }
     }
 
-    fn lookup_variable(&mut self, expr: &Expr, name: &str) -> InterpreterResult<Literal> {
-        let distance = self.locals.get(expr);
-        let res;
-        if let Some(distance) = distance {
-            res = self.environment.borrow_mut().get_at(*distance, name);
-        } else {
-            res = self.environment.borrow().get(name);
+    // In debug mode, reports references the resolver placed in another scope than a
+    // lookup by name finds. Closures declared before a shadowing local legitimately
+    // differ, so this only records and prints a diagnostic.
+    fn check_resolution(&mut self, name: &Token, local: Option<(usize, usize)>) {
+        if !self.debug {
+            return;
+        }
+        let lexeme = name.lexeme.as_deref().unwrap_or_default();
+        let resolved = local.map(|(distance, _)| distance);
+        let dynamic = self.environment.borrow().local_distance(lexeme);
+        if resolved != dynamic {
+            let describe = |distance: Option<usize>| match distance {
+                Some(distance) => format!("the local {} scope(s) out", distance),
+                None => "a global".to_string(),
+            };
+            let diagnostic = format!(
+                "[line {}] Debug: '{}' resolved to {} but lookup by name finds {}",
+                name.line,
+                lexeme,
+                describe(resolved),
+                describe(dynamic)
+            );
+            eprintln!("{}", diagnostic);
+            self.diagnostics.push(diagnostic);
         }
-        res.ok_or_else(|| EarlyReturn::Error(InterpreterError::UndefinedVariable(name.to_string())))
     }
 
     fn unary_expr(&mut self, operator: &Token, right: &Expr) -> InterpreterResult<Literal> {
         let right = self.evaluate(right)?;
+        Self::unary_op(&operator.token_type, right)
+    }
+
+    // Operators work on values alone, so the optimizer can apply them to literals ahead of
+    // time
+    pub(crate) fn unary_op(operator: &TokenType, right: Literal) -> InterpreterResult<Literal> {
         use frontend::token::TokenType::*;
-        match operator.token_type {
-            Minus => Ok(Literal::Number(-(f64::try_from(right)?))),
-            Bang => Ok(Literal::Boolean(!(bool::try_from(TryFromWrapper(right))?))),
+        match (operator, right) {
+            (Minus, Literal::Integer(i)) => i
+                .checked_neg()
+                .map(Literal::Integer)
+                .ok_or(EarlyReturn::Error(InterpreterError::IntegerOverflow)),
+            (Minus, right) => Ok(Literal::Number(-(f64::try_from(right)?))),
+            (Bang, right) => Ok(Literal::Boolean(!(bool::try_from(TryFromWrapper(right))?))),
             _ => Err(EarlyReturn::Error(InterpreterError::InvalidAstType)),
         }
     }
@@ -384,31 +882,26 @@ impl Interpreter {
     ) -> InterpreterResult<Literal> {
         let left = self.evaluate(left)?;
         let right = self.evaluate(right)?;
+        Self::binary_op(&operator.token_type, left, right)
+    }
 
+    pub(crate) fn binary_op(
+        operator: &TokenType,
+        left: Literal,
+        right: Literal,
+    ) -> InterpreterResult<Literal> {
         use frontend::token::TokenType::*;
-        match operator.token_type {
-            Minus => {
-                let left = f64::try_from(left)?;
-                let right = f64::try_from(right)?;
-                Ok(Literal::Number(left - right))
-            }
-            Slash => {
-                let left = f64::try_from(left)?;
-                let right = f64::try_from(right)?;
-                Ok(Literal::Number(left / right))
-            }
-            Star => {
-                let left = f64::try_from(left)?;
-                let right = f64::try_from(right)?;
-                Ok(Literal::Number(left * right))
-            }
+        match operator {
+            Minus | Slash | Star | Percent | TildeSlash => Self::arithmetic(operator, left, right),
             Plus => match (left, right) {
                 (Literal::String(l), Literal::String(r)) => {
                     Ok(Literal::String(format!("{}{}", l, r)))
                 }
-                (Literal::Number(l), Literal::Number(r)) => Ok(Literal::Number(l + r)),
-                _ => Err(EarlyReturn::Error(InterpreterError::InvalidAstType)),
+                (l, r) => Self::arithmetic(operator, l, r),
             },
+            Ampersand | Pipe | Caret | LessLess | GreaterGreater => {
+                Self::bitwise(operator, left, right)
+            }
             Greater => Ok(Literal::Boolean(left > right)),
             GreaterEqual => Ok(Literal::Boolean(left >= right)),
             Less => Ok(Literal::Boolean(left < right)),
@@ -418,4 +911,62 @@ impl Interpreter {
             _ => Err(EarlyReturn::Error(InterpreterError::InvalidAstType)),
         }
     }
+
+    // Integer operands stay integers and fail on overflow, mixing in a float promotes both
+    // sides to floats. '/' always produces a float, '~/' truncates towards zero.
+    fn arithmetic(
+        operator: &TokenType,
+        left: Literal,
+        right: Literal,
+    ) -> InterpreterResult<Literal> {
+        use frontend::token::TokenType::*;
+        if let (Literal::Integer(l), Literal::Integer(r)) = (&left, &right) {
+            let (l, r) = (*l, *r);
+            if r == 0 && (*operator == Percent || *operator == TildeSlash) {
+                return Err(EarlyReturn::Error(InterpreterError::DivisionByZero));
+            }
+            let res = match operator {
+                Plus => l.checked_add(r),
+                Minus => l.checked_sub(r),
+                Star => l.checked_mul(r),
+                Percent => l.checked_rem(r),
+                TildeSlash => l.checked_div(r),
+                Slash => return Ok(Literal::Number(l as f64 / r as f64)),
+                _ => return Err(EarlyReturn::Error(InterpreterError::InvalidAstType)),
+            };
+            return res
+                .map(Literal::Integer)
+                .ok_or(EarlyReturn::Error(InterpreterError::IntegerOverflow));
+        }
+
+        let left = f64::try_from(left)?;
+        let right = f64::try_from(right)?;
+        match operator {
+            Plus => Ok(Literal::Number(left + right)),
+            Minus => Ok(Literal::Number(left - right)),
+            Star => Ok(Literal::Number(left * right)),
+            Slash => Ok(Literal::Number(left / right)),
+            Percent => Ok(Literal::Number(left % right)),
+            TildeSlash => Ok(Literal::Number((left / right).trunc())),
+            _ => Err(EarlyReturn::Error(InterpreterError::InvalidAstType)),
+        }
+    }
+
+    fn bitwise(operator: &TokenType, left: Literal, right: Literal) -> InterpreterResult<Literal> {
+        let left = i64::try_from(left)?;
+        let right = i64::try_from(right)?;
+
+        use frontend::token::TokenType::*;
+        let res = match operator {
+            Ampersand => Some(left & right),
+            Pipe => Some(left | right),
+            Caret => Some(left ^ right),
+            // Shifting by a negative amount or past the width of an integer overflows
+            LessLess => u32::try_from(right).ok().and_then(|r| left.checked_shl(r)),
+            GreaterGreater => u32::try_from(right).ok().and_then(|r| left.checked_shr(r)),
+            _ => return Err(EarlyReturn::Error(InterpreterError::InvalidAstType)),
+        };
+        res.map(Literal::Integer)
+            .ok_or(EarlyReturn::Error(InterpreterError::IntegerOverflow))
+    }
 }
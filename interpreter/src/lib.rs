@@ -2,7 +2,11 @@ extern crate frontend;
 extern crate utils;
 
 pub mod clock;
+pub mod exception;
 pub mod interpreter;
+pub mod module_loader;
+pub mod optimizer;
 
 // Semantic Analysis
+pub mod checker;
 pub mod resolver;
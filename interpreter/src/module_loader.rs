@@ -0,0 +1,171 @@
+use crate::resolver::{Resolver, ResolverWarning};
+use frontend::ast::{NodeId, Stmt};
+use frontend::module::Module;
+use frontend::parser::Parser;
+use frontend::scanner::Scanner;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+use utils::errors::InterpreterError;
+
+// Source of a module that is ready to run
+pub struct LoadedSource {
+    pub path: PathBuf,
+    pub ast: Vec<Stmt>,
+    pub locals: HashMap<NodeId, (usize, usize)>,
+}
+
+// Caches imported modules by canonical path and tracks the chain of files being
+// loaded, so every module runs once and import cycles are reported
+#[derive(Default)]
+pub struct ModuleLoader {
+    cache: HashMap<PathBuf, Module>,
+    loading: Vec<PathBuf>,
+    // Modules checked before the program runs, waiting to be loaded
+    prepared: HashMap<PathBuf, LoadedSource>,
+    // Resolver warnings of the checked modules, until the driver reports them
+    warnings: Vec<(PathBuf, ResolverWarning)>,
+    // Refuses to run modules with warnings
+    pub deny_warnings: bool,
+}
+
+impl ModuleLoader {
+    // The file passed to `lox`, imports inside it resolve relative to its directory
+    pub fn set_entry(&mut self, path: &Path) {
+        self.loading = fs::canonicalize(path).into_iter().collect();
+    }
+
+    pub fn resolve_path(&self, path: &str) -> Result<PathBuf, InterpreterError> {
+        resolve_from(self.loading.last().map(PathBuf::as_path), path)
+    }
+
+    pub fn cached(&self, path: &Path) -> Option<Module> {
+        self.cache.get(path).cloned()
+    }
+
+    // Checks every module the program imports, directly or through other modules, before
+    // any of it runs, so their warnings are reported up front. Modules that fail to load
+    // are skipped here and report their error when they are imported.
+    pub fn prepare(&mut self, imports: Vec<String>) {
+        let importer = self.loading.last().cloned();
+        self.prepare_from(importer.as_deref(), imports);
+    }
+
+    fn prepare_from(&mut self, importer: Option<&Path>, imports: Vec<String>) {
+        for import in imports {
+            let Ok(path) = resolve_from(importer, &import) else {
+                continue;
+            };
+            let known = self.cache.contains_key(&path)
+                || self.prepared.contains_key(&path)
+                || self.loading.contains(&path);
+            if known {
+                continue;
+            }
+            if let Ok((source, imports)) = self.read(path.clone()) {
+                self.prepared.insert(path.clone(), source);
+                self.prepare_from(Some(&path), imports);
+            }
+        }
+    }
+
+    // Hands out a module to run and marks it as loading
+    pub fn load(&mut self, path: PathBuf) -> Result<LoadedSource, InterpreterError> {
+        if self.loading.contains(&path) {
+            let mut chain: Vec<String> = self.loading.iter().map(|p| display(p)).collect();
+            chain.push(display(&path));
+            return Err(InterpreterError::ImportCycle(chain.join(" -> ")));
+        }
+
+        let source = match self.prepared.remove(&path) {
+            Some(source) => source,
+            None => {
+                let warned = self.warnings.len();
+                let (source, _) = self.read(path.clone())?;
+                if self.deny_warnings && self.warnings.len() > warned {
+                    let message = "Warnings are denied".to_string();
+                    return Err(InterpreterError::InvalidModule(display(&path), message));
+                }
+                source
+            }
+        };
+        self.loading.push(path);
+        Ok(source)
+    }
+
+    // Reads, parses and resolves a module, returning it with the paths it imports
+    fn read(&mut self, path: PathBuf) -> Result<(LoadedSource, Vec<String>), InterpreterError> {
+        let invalid = |msg: String| InterpreterError::InvalidModule(display(&path), msg);
+        let source = fs::read_to_string(&path).map_err(|e| invalid(e.to_string()))?;
+        let tokens = Scanner::new(source.as_str())
+            .scan_tokens()
+            .map_err(|e| invalid(format!("[line {}] {}", e.line(), e)))?;
+        let ast = Parser::new(tokens).parse().map_err(|errs| {
+            let messages: Vec<String> = errs
+                .iter()
+                .map(|e| format!("[line {}] {}", e.line(), e))
+                .collect();
+            invalid(messages.join(", "))
+        })?;
+        let mut resolver = Resolver::new();
+        resolver.resolve_stmts(&ast).map_err(|errs| {
+            let messages: Vec<String> = errs
+                .iter()
+                .map(|e| format!("[line {}] {}", e.token().line, e))
+                .collect();
+            invalid(messages.join(", "))
+        })?;
+        let warnings = resolver.take_warnings();
+        self.warnings
+            .extend(warnings.into_iter().map(|w| (path.clone(), w)));
+
+        let imports = resolver.take_imports();
+        let source = LoadedSource {
+            path,
+            ast,
+            locals: resolver.into_locals(),
+        };
+        Ok((source, imports))
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<(PathBuf, ResolverWarning)> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    // Called once a module has run, successfully or not
+    pub fn finish(&mut self, path: PathBuf, module: Option<Module>) {
+        self.loading.pop();
+        if let Some(module) = module {
+            self.cache.insert(path, module);
+        }
+    }
+}
+
+// Imports resolve relative to the directory of the importing file
+fn resolve_from(importer: Option<&Path>, path: &str) -> Result<PathBuf, InterpreterError> {
+    let base = match importer.and_then(|p| p.parent()) {
+        Some(dir) => dir.to_path_buf(),
+        None => env::current_dir().unwrap_or_default(),
+    };
+    fs::canonicalize(base.join(path))
+        .map_err(|_| InterpreterError::ModuleNotFound(path.to_string()))
+}
+
+fn display(path: &Path) -> String {
+    path.display().to_string()
+}
+
+// Names declared by the `export` statements of a module
+pub fn exported_names(ast: &[Stmt]) -> Vec<String> {
+    ast.iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::Export(declaration) => match declaration.as_ref() {
+                Stmt::Var(name, ..) | Stmt::Function(name, ..) | Stmt::Class(name, ..) => {
+                    name.lexeme.clone()
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
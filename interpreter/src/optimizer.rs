@@ -0,0 +1,225 @@
+use crate::interpreter::Interpreter;
+use frontend::ast::{Expr, Stmt};
+use frontend::literal::Literal;
+use frontend::token::TokenType;
+use std::rc::Rc;
+
+// What the optimizer changed, reported with `--verbose`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OptimizerStats {
+    // Unary, binary and logical expressions replaced by their value
+    pub folded: usize,
+    // If statements replaced by the branch their constant condition selects
+    pub removed_branches: usize,
+    // While loops whose condition is constantly false
+    pub removed_loops: usize,
+}
+
+// Folds operators applied to literals and drops code that can never run. It runs after
+// the resolver and keeps every variable node, so resolved slots stay valid. Operations
+// that would fail, such as an integer overflow or a division by zero, are left in place
+// to be reported with their line at runtime.
+#[derive(Default)]
+pub struct Optimizer {
+    stats: OptimizerStats,
+}
+
+impl Optimizer {
+    pub fn new() -> Self {
+        Optimizer::default()
+    }
+
+    pub fn stats(&self) -> OptimizerStats {
+        self.stats
+    }
+
+    pub fn optimize(&mut self, stmts: Vec<Stmt>) -> Vec<Stmt> {
+        stmts.into_iter().filter_map(|s| self.stmt(s)).collect()
+    }
+
+    fn body(&mut self, body: Rc<[Stmt]>) -> Rc<[Stmt]> {
+        self.optimize(body.to_vec()).into()
+    }
+
+    // A statement nested in another one can't just disappear, so removed code becomes an
+    // empty block
+    fn branch(&mut self, stmt: Stmt) -> Stmt {
+        self.stmt(stmt).unwrap_or_else(|| Stmt::Block(Vec::new()))
+    }
+
+    // Returns None when the statement can never run
+    fn stmt(&mut self, stmt: Stmt) -> Option<Stmt> {
+        let stmt = match stmt {
+            Stmt::Block(stmts) => Stmt::Block(self.optimize(stmts)),
+            Stmt::Expr(expr) => Stmt::Expr(self.expr(expr)),
+            Stmt::Print(expr) => Stmt::Print(self.expr(expr)),
+            Stmt::Function(name, params, body, signature) => {
+                Stmt::Function(name, params, self.body(body), signature)
+            }
+            Stmt::If(condition, consequent, alternative) => {
+                let condition = self.expr(condition);
+                if let Expr::Literal(ref value) = condition {
+                    self.stats.removed_branches += 1;
+                    return if bool::from(value.clone()) {
+                        self.stmt(*consequent)
+                    } else {
+                        alternative.and_then(|alt| self.stmt(*alt))
+                    };
+                }
+                Stmt::If(
+                    condition,
+                    Box::new(self.branch(*consequent)),
+                    alternative.map(|alt| Box::new(self.branch(*alt))),
+                )
+            }
+            Stmt::Return(keyword, value) => Stmt::Return(keyword, value.map(|v| self.expr(v))),
+            Stmt::Var(name, annotation, init) => {
+                Stmt::Var(name, annotation, init.map(|init| self.expr(init)))
+            }
+            Stmt::While(condition, body, increment) => {
+                let condition = self.expr(condition);
+                if let Expr::Literal(ref value) = condition {
+                    if !bool::from(value.clone()) {
+                        self.stats.removed_loops += 1;
+                        return None;
+                    }
+                }
+                Stmt::While(
+                    condition,
+                    Box::new(self.branch(*body)),
+                    increment.map(|inc| self.expr(inc)),
+                )
+            }
+            Stmt::ForIn(name, iterable, body) => {
+                Stmt::ForIn(name, self.expr(iterable), Box::new(self.branch(*body)))
+            }
+            Stmt::Class(name, superclass, methods) => Stmt::Class(
+                name,
+                superclass,
+                methods
+                    .into_iter()
+                    .map(|(kind, method)| (kind, self.branch(method)))
+                    .collect(),
+            ),
+            Stmt::Throw(keyword, value) => Stmt::Throw(keyword, self.expr(value)),
+            Stmt::Export(declaration) => Stmt::Export(Box::new(self.branch(*declaration))),
+            Stmt::Try(body, catch, finally) => Stmt::Try(
+                self.optimize(body),
+                catch.map(|(name, handler)| (name, self.optimize(handler))),
+                finally.map(|finally| self.optimize(finally)),
+            ),
+            Stmt::Match(keyword, subject, cases, default) => Stmt::Match(
+                keyword,
+                self.expr(subject),
+                cases
+                    .into_iter()
+                    .map(|(patterns, body)| (patterns, self.branch(body)))
+                    .collect(),
+                default.map(|default| Box::new(self.branch(*default))),
+            ),
+            stmt @ Stmt::Import(..) | stmt @ Stmt::Break(_) | stmt @ Stmt::Continue(_) => stmt,
+        };
+        Some(stmt)
+    }
+
+    fn boxed(&mut self, expr: Box<Expr>) -> Box<Expr> {
+        Box::new(self.expr(*expr))
+    }
+
+    fn expr(&mut self, expr: Expr) -> Expr {
+        match expr {
+            Expr::Binary(left, operator, right) => {
+                let (left, right) = (self.expr(*left), self.expr(*right));
+                if let (Expr::Literal(l), Expr::Literal(r)) = (&left, &right) {
+                    if let Ok(value) =
+                        Interpreter::binary_op(&operator.token_type, l.clone(), r.clone())
+                    {
+                        self.stats.folded += 1;
+                        return Expr::Literal(value);
+                    }
+                }
+                Expr::Binary(Box::new(left), operator, Box::new(right))
+            }
+            Expr::Unary(operator, right) => {
+                let right = self.expr(*right);
+                if let Expr::Literal(ref r) = right {
+                    if let Ok(value) = Interpreter::unary_op(&operator.token_type, r.clone()) {
+                        self.stats.folded += 1;
+                        return Expr::Literal(value);
+                    }
+                }
+                Expr::Unary(operator, Box::new(right))
+            }
+            // Parentheses around a constant no longer group anything
+            Expr::Grouping(inner) => match self.expr(*inner) {
+                literal @ Expr::Literal(_) => literal,
+                inner => Expr::Grouping(Box::new(inner)),
+            },
+            Expr::Assign(name, value, id) => Expr::Assign(name, self.boxed(value), id),
+            Expr::Call(callee, paren, args) => Expr::Call(
+                self.boxed(callee),
+                paren,
+                args.into_iter().map(|arg| self.expr(arg)).collect(),
+            ),
+            Expr::Comma(left, comma, right) => {
+                Expr::Comma(self.boxed(left), comma, self.boxed(right))
+            }
+            Expr::CompoundAssign(target, operator, value) => {
+                Expr::CompoundAssign(self.boxed(target), operator, self.boxed(value))
+            }
+            Expr::Conditional(condition, question, then_branch, else_branch) => Expr::Conditional(
+                self.boxed(condition),
+                question,
+                self.boxed(then_branch),
+                self.boxed(else_branch),
+            ),
+            Expr::Get(object, name) => Expr::Get(self.boxed(object), name),
+            // A constant becomes its string, so the concatenation around it can fold too
+            Expr::Stringify(inner) => match self.expr(*inner) {
+                Expr::Literal(value) => Expr::Literal(Literal::String(value.to_string())),
+                inner => Expr::Stringify(Box::new(inner)),
+            },
+            Expr::Increment(target, operator, prefix) => {
+                Expr::Increment(self.boxed(target), operator, prefix)
+            }
+            Expr::Lambda(keyword, params, body, signature) => {
+                Expr::Lambda(keyword, params, self.body(body), signature)
+            }
+            Expr::List(elements) => {
+                Expr::List(elements.into_iter().map(|e| self.expr(e)).collect())
+            }
+            Expr::Logical(left, operator, right) => {
+                let (left, right) = (self.expr(*left), self.expr(*right));
+                if let Expr::Literal(ref l) = left {
+                    self.stats.folded += 1;
+                    // The left operand is the value unless it leaves the decision to the right
+                    let decided = bool::from(l.clone()) == (operator.token_type == TokenType::Or);
+                    return if decided { left } else { right };
+                }
+                Expr::Logical(Box::new(left), operator, Box::new(right))
+            }
+            Expr::Map(entries) => Expr::Map(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (self.expr(key), self.expr(value)))
+                    .collect(),
+            ),
+            Expr::Set(object, name, value) => {
+                Expr::Set(self.boxed(object), name, self.boxed(value))
+            }
+            Expr::SetSubscript(object, bracket, index, value) => Expr::SetSubscript(
+                self.boxed(object),
+                bracket,
+                self.boxed(index),
+                self.boxed(value),
+            ),
+            Expr::Subscript(object, bracket, index) => {
+                Expr::Subscript(self.boxed(object), bracket, self.boxed(index))
+            }
+            expr @ Expr::Literal(_)
+            | expr @ Expr::Super(..)
+            | expr @ Expr::This(..)
+            | expr @ Expr::Variable(..) => expr,
+        }
+    }
+}
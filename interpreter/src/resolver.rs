@@ -1,19 +1,145 @@
-use crate::interpreter::Interpreter;
-use frontend::ast::{Expr, Stmt};
+use frontend::ast::{Expr, MethodKind, NodeId, Pattern, Stmt};
 use frontend::token::Token;
-use std::cell::RefCell;
 use std::collections::HashMap;
-use std::rc::Rc;
+use std::fmt;
+use std::mem;
 
+#[derive(Debug)]
 pub enum ResolverError {
-    UndefinedVariable,
-    ExistingVariable,
-    InvalidReturnStatement,
-    InvalidThisStatement,
-    InvalidSuperStatement,
+    // A local read in its own initializer
+    UndefinedVariable(Token),
+    ExistingVariable(Token),
+    InheritsFromItself(Token),
+    InvalidReturnStatement(Token),
+    ReturnFromInitializer(Token),
+    InvalidThisStatement(Token),
+    InvalidSuperStatement(Token),
+    InvalidBreakStatement(Token),
+    InvalidContinueStatement(Token),
+    // Name of the declaration exported outside of the top level
+    InvalidExportStatement(Token),
+    // `match` keyword and the repeated pattern
+    DuplicateMatchPattern(Token, String),
 }
 
-type ResolverResult<T> = Result<T, ResolverError>;
+impl ResolverError {
+    pub fn token(&self) -> &Token {
+        match self {
+            ResolverError::UndefinedVariable(token)
+            | ResolverError::ExistingVariable(token)
+            | ResolverError::InheritsFromItself(token)
+            | ResolverError::InvalidReturnStatement(token)
+            | ResolverError::ReturnFromInitializer(token)
+            | ResolverError::InvalidThisStatement(token)
+            | ResolverError::InvalidSuperStatement(token)
+            | ResolverError::InvalidBreakStatement(token)
+            | ResolverError::InvalidContinueStatement(token)
+            | ResolverError::InvalidExportStatement(token)
+            | ResolverError::DuplicateMatchPattern(token, _) => token,
+        }
+    }
+}
+
+impl fmt::Display for ResolverError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResolverError::UndefinedVariable(name) => write!(
+                f,
+                "Can't read local variable '{}' in its own initializer",
+                lexeme(name)
+            ),
+            ResolverError::ExistingVariable(name) => write!(
+                f,
+                "Already a variable named '{}' in this scope",
+                lexeme(name)
+            ),
+            ResolverError::InheritsFromItself(name) => {
+                write!(f, "Class '{}' can't inherit from itself", lexeme(name))
+            }
+            ResolverError::InvalidReturnStatement(_) => {
+                write!(f, "Can't return from top-level code")
+            }
+            ResolverError::ReturnFromInitializer(_) => {
+                write!(f, "Can't return a value from an initializer")
+            }
+            ResolverError::InvalidThisStatement(_) => {
+                write!(f, "Can't use 'this' outside of a method")
+            }
+            ResolverError::InvalidSuperStatement(_) => {
+                write!(f, "Can't use 'super' outside of a subclass")
+            }
+            ResolverError::InvalidBreakStatement(_) => {
+                write!(f, "Can't use 'break' outside of a loop")
+            }
+            ResolverError::InvalidContinueStatement(_) => {
+                write!(f, "Can't use 'continue' outside of a loop")
+            }
+            ResolverError::InvalidExportStatement(name) => write!(
+                f,
+                "Can't export '{}', only top-level declarations can be exported",
+                lexeme(name)
+            ),
+            ResolverError::DuplicateMatchPattern(_, pattern) => write!(
+                f,
+                "Pattern '{}' is already matched by an earlier case",
+                pattern
+            ),
+        }
+    }
+}
+
+// Suspicious but valid code, reported without stopping the resolver
+#[derive(Debug)]
+pub enum ResolverWarning {
+    UnusedVariable(Token),
+    // The return statement followed by the unreachable code
+    UnreachableCode(Token),
+    ShadowedVariable(Token),
+    SelfReference(Token),
+}
+
+impl ResolverWarning {
+    pub fn token(&self) -> &Token {
+        match self {
+            ResolverWarning::UnusedVariable(token)
+            | ResolverWarning::UnreachableCode(token)
+            | ResolverWarning::ShadowedVariable(token)
+            | ResolverWarning::SelfReference(token) => token,
+        }
+    }
+}
+
+impl fmt::Display for ResolverWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResolverWarning::UnusedVariable(name) => {
+                write!(f, "Local variable '{}' is never read", lexeme(name))
+            }
+            ResolverWarning::UnreachableCode(_) => write!(f, "Unreachable code after 'return'"),
+            ResolverWarning::ShadowedVariable(name) => write!(
+                f,
+                "Variable '{}' shadows a local of an enclosing scope",
+                lexeme(name)
+            ),
+            ResolverWarning::SelfReference(name) => {
+                write!(
+                    f,
+                    "Variable '{}' is read in its own initializer",
+                    lexeme(name)
+                )
+            }
+        }
+    }
+}
+
+// A local declared in a scope, stored at the given slot of its environment
+struct Local {
+    defined: bool,
+    slot: usize,
+    read: bool,
+    // Declaration to warn about if the local is never read, unless it is exempt
+    declaration: Option<Token>,
+}
 
 #[derive(Clone)]
 enum FunctionType {
@@ -30,163 +156,333 @@ enum ClassType {
     SubClass,
 }
 
+// Errors are collected rather than returned, so one pass reports all of them and the
+// scopes stay balanced after an error
 pub struct Resolver {
-    interpreter: Rc<RefCell<Interpreter>>,
-    scopes: Vec<HashMap<String, bool>>,
+    // Scope distance and slot of every resolved local, handed to the interpreter afterwards
+    locals: HashMap<NodeId, (usize, usize)>,
+    scopes: Vec<HashMap<String, Local>>,
     current_function: FunctionType,
     current_class: ClassType,
+    loop_depth: usize,
+    // Global whose initializer is being resolved
+    initializing: Option<String>,
+    errors: Vec<ResolverError>,
+    warnings: Vec<ResolverWarning>,
+    // Paths of every import statement, so modules can be checked before running
+    imports: Vec<String>,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Resolver::new()
+    }
 }
 
 impl Resolver {
-    pub fn new(interpreter: Rc<RefCell<Interpreter>>) -> Self {
+    pub fn new() -> Self {
         Resolver {
-            interpreter,
+            locals: HashMap::new(),
             scopes: Vec::new(),
             current_function: FunctionType::None,
             current_class: ClassType::None,
+            loop_depth: 0,
+            initializing: None,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            imports: Vec::new(),
+        }
+    }
+
+    pub fn into_locals(self) -> HashMap<NodeId, (usize, usize)> {
+        self.locals
+    }
+
+    pub fn warnings(&self) -> &[ResolverWarning] {
+        &self.warnings
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<ResolverWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    pub fn take_imports(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.imports)
+    }
+
+    pub fn resolve_stmts(&mut self, stmts: &[Stmt]) -> Result<(), Vec<ResolverError>> {
+        self.resolve_body(stmts);
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(mem::take(&mut self.errors))
         }
     }
 
-    pub fn resolve_stmts(&mut self, stmts: &[Stmt]) -> ResolverResult<()> {
+    fn resolve_body(&mut self, stmts: &[Stmt]) {
         for s in stmts {
-            self.resolve_stmt(s)?;
+            self.resolve_stmt(s);
+        }
+        // Reported once, at the first return that has statements after it
+        if let Some((_, leading)) = stmts.split_last() {
+            if let Some(Stmt::Return(keyword, _)) =
+                leading.iter().find(|s| matches!(s, Stmt::Return(..)))
+            {
+                self.warnings
+                    .push(ResolverWarning::UnreachableCode(keyword.clone()));
+            }
         }
-        Ok(())
     }
 
-    fn resolve_stmt(&mut self, stmt: &Stmt) -> ResolverResult<()> {
+    fn resolve_stmt(&mut self, stmt: &Stmt) {
         match stmt {
             Stmt::Block(stmts) => self.block(stmts),
-            Stmt::Var(name, init) => self.var_stmt(name, init),
-            Stmt::Function(ref name, args, body) => self.function_stmt(name, args, body),
+            Stmt::Var(name, _, init) => self.var_stmt(name, init),
+            Stmt::Function(ref name, args, body, _) => self.function_stmt(name, args, body),
             Stmt::Expr(ref expr) => self.resolve_expr(expr),
             Stmt::If(ref condition, consequent, alternate) => {
                 self.if_stmt(condition, consequent, alternate)
             }
             Stmt::Print(ref expr) => self.resolve_expr(expr),
-            Stmt::Return(_name, expr) => {
+            Stmt::Return(keyword, expr) => {
                 if let FunctionType::None = self.current_function {
-                    return Err(ResolverError::InvalidReturnStatement);
+                    self.error(ResolverError::InvalidReturnStatement(keyword.clone()));
                 }
                 if let Some(e) = expr {
                     if let FunctionType::Init = self.current_function {
-                        return Err(ResolverError::InvalidReturnStatement);
+                        self.error(ResolverError::ReturnFromInitializer(keyword.clone()));
+                    }
+                    self.resolve_expr(e);
+                }
+            }
+            Stmt::While(ref condition, body, increment) => {
+                self.resolve_expr(condition);
+                self.loop_depth += 1;
+                self.resolve_stmt(body);
+                self.loop_depth -= 1;
+                if let Some(increment) = increment {
+                    self.resolve_expr(increment);
+                }
+            }
+            Stmt::ForIn(ref name, iterable, body) => {
+                self.resolve_expr(iterable);
+                // The loop variable lives in its own scope around the body
+                self.begin_scope();
+                self.declare(name, false);
+                self.define(name);
+                self.loop_depth += 1;
+                self.resolve_stmt(body);
+                self.loop_depth -= 1;
+                self.end_scope();
+            }
+            Stmt::Break(keyword) => {
+                if self.loop_depth == 0 {
+                    self.error(ResolverError::InvalidBreakStatement(keyword.clone()));
+                }
+            }
+            Stmt::Continue(keyword) => {
+                if self.loop_depth == 0 {
+                    self.error(ResolverError::InvalidContinueStatement(keyword.clone()));
+                }
+            }
+            Stmt::Throw(_keyword, ref value) => self.resolve_expr(value),
+            Stmt::Import(_keyword, path, ref name) => {
+                self.imports.push(path.clone());
+                self.declare(name, true);
+                self.define(name);
+            }
+            Stmt::Export(declaration) => {
+                if !self.scopes.is_empty() {
+                    if let Stmt::Var(name, ..) | Stmt::Function(name, ..) | Stmt::Class(name, ..) =
+                        declaration.as_ref()
+                    {
+                        self.error(ResolverError::InvalidExportStatement(name.clone()));
                     }
-                    self.resolve_expr(e)?;
                 }
-                Ok(())
+                self.resolve_stmt(declaration)
             }
-            Stmt::While(ref condition, body) => {
-                self.resolve_expr(condition)?;
-                self.resolve_stmt(body)?;
-                Ok(())
+            Stmt::Try(body, catch, finally) => {
+                self.block(body);
+                if let Some((name, handler)) = catch {
+                    // The caught value is scoped to the handler
+                    self.begin_scope();
+                    self.declare(name, false);
+                    self.define(name);
+                    self.resolve_body(handler);
+                    self.end_scope();
+                }
+                if let Some(finally) = finally {
+                    self.block(finally);
+                }
             }
             Stmt::Class(ref name, super_class, methods) => {
                 self.class_stmt(name, super_class, methods)
             }
+            Stmt::Match(_, ref subject, cases, default) => self.match_stmt(subject, cases, default),
+        }
+    }
+
+    fn match_stmt(
+        &mut self,
+        subject: &Expr,
+        cases: &[(Vec<Pattern>, Stmt)],
+        default: &Option<Box<Stmt>>,
+    ) {
+        self.resolve_expr(subject);
+        // A literal that repeats an earlier pattern could never be reached
+        let mut seen = Vec::new();
+        for (patterns, body) in cases {
+            for pattern in patterns {
+                match pattern {
+                    Pattern::Literal(token, literal) => {
+                        if seen.contains(&literal) {
+                            self.error(ResolverError::DuplicateMatchPattern(
+                                token.clone(),
+                                pattern.to_string(),
+                            ));
+                        }
+                        seen.push(literal);
+                    }
+                    Pattern::Class(class) => self.resolve_expr(class),
+                }
+            }
+            self.resolve_stmt(body);
+        }
+        if let Some(default) = default {
+            self.resolve_stmt(default);
         }
     }
 
-    fn resolve_expr(&self, expr: &Expr) -> ResolverResult<()> {
+    fn resolve_expr(&mut self, expr: &Expr) {
         match expr {
-            Expr::Variable(ref name) => self.var_expr(expr, name),
-            Expr::Assign(ref name, ref init) => self.assign_expr(expr, name, init),
+            Expr::Variable(ref name, id) => self.var_expr(*id, name),
+            Expr::Assign(ref name, ref init, id) => self.assign_expr(*id, name, init),
             Expr::Binary(left, _operator, right) => {
-                self.resolve_expr(left)?;
-                self.resolve_expr(right)?;
-                Ok(())
+                self.resolve_expr(left);
+                self.resolve_expr(right);
             }
             Expr::Call(callee, _paren, args) => {
-                self.resolve_expr(callee)?;
+                self.resolve_expr(callee);
                 for a in args {
-                    self.resolve_expr(a)?;
+                    self.resolve_expr(a);
                 }
-                Ok(())
             }
-            Expr::Grouping(expr) => self.resolve_expr(expr),
-            Expr::Literal(_literal) => Ok(()), // No op, we do not need to resolve literals
+            Expr::Comma(left, _comma, right) => {
+                self.resolve_expr(left);
+                self.resolve_expr(right);
+            }
+            Expr::CompoundAssign(target, _operator, value) => {
+                self.resolve_expr(value);
+                self.resolve_expr(target)
+            }
+            Expr::Increment(target, ..) => self.resolve_expr(target),
+            Expr::Conditional(condition, _question, then_branch, else_branch) => {
+                self.resolve_expr(condition);
+                self.resolve_expr(then_branch);
+                self.resolve_expr(else_branch);
+            }
+            Expr::Grouping(expr) | Expr::Stringify(expr) => self.resolve_expr(expr),
+            Expr::Literal(_literal) => {} // No op, we do not need to resolve literals
             Expr::Logical(left, _op, right) => {
-                self.resolve_expr(left)?;
-                self.resolve_expr(right)?;
-                Ok(())
+                self.resolve_expr(left);
+                self.resolve_expr(right);
             }
             Expr::Unary(_op, right) => self.resolve_expr(right),
             Expr::Get(object, _name) => self.resolve_expr(object),
             Expr::Set(object, _name, new_value) => {
-                self.resolve_expr(object)?;
-                self.resolve_expr(new_value)?;
-                Ok(())
+                self.resolve_expr(object);
+                self.resolve_expr(new_value);
             }
-            Expr::Super(keyword, ..) => self.super_expr(keyword, expr),
-            Expr::This(name) => {
+            Expr::Super(keyword, _method, id) => self.super_expr(keyword, *id),
+            Expr::This(name, id) => {
                 if let ClassType::None = self.current_class {
-                    return Err(ResolverError::InvalidThisStatement);
+                    self.error(ResolverError::InvalidThisStatement(name.clone()));
+                }
+                self.resolve_local(*id, name, true)
+            }
+            Expr::Lambda(_keyword, params, body, _) => self.lambda(params, body),
+            Expr::List(elements) => {
+                for e in elements {
+                    self.resolve_expr(e);
+                }
+            }
+            Expr::Map(entries) => {
+                for (key, value) in entries {
+                    self.resolve_expr(key);
+                    self.resolve_expr(value);
                 }
-                self.resolve_local(expr, name)
+            }
+            Expr::Subscript(object, _bracket, index) => {
+                self.resolve_expr(object);
+                self.resolve_expr(index);
+            }
+            Expr::SetSubscript(object, _bracket, index, new_value) => {
+                self.resolve_expr(object);
+                self.resolve_expr(index);
+                self.resolve_expr(new_value);
             }
         }
     }
 
-    fn resolve_local(&self, expr: &Expr, name: &Token) -> ResolverResult<()> {
-        for i in (0..self.scopes.len()).rev() {
-            if let Some(name_str) = name.lexeme.clone() {
-                if let Some(curr_scope) = self.scopes.get(i) {
-                    if curr_scope.contains_key(name_str.as_str()) {
-                        self.interpreter
-                            .borrow_mut()
-                            .resolve(expr.clone(), self.scopes.len() - 1 - i);
-                    }
-                }
+    // Records the innermost scope declaring the name, leaving globals unresolved. Writes
+    // do not count as reading the local.
+    fn resolve_local(&mut self, id: NodeId, name: &Token, read: bool) {
+        let name = lexeme(name);
+        for (depth, scope) in self.scopes.iter_mut().rev().enumerate() {
+            if let Some(local) = scope.get_mut(name) {
+                local.read |= read;
+                self.locals.insert(id, (depth, local.slot));
+                break;
             }
         }
-        Ok(())
     }
 
     fn class_stmt(
         &mut self,
         name: &Token,
         super_class: &Option<Expr>,
-        methods: &[Stmt],
-    ) -> ResolverResult<()> {
+        methods: &[(MethodKind, Stmt)],
+    ) {
         let enclosing_class = self.current_class.clone();
-        self.current_class = ClassType::Class;
-        self.declare(name)?;
+        self.declare(name, true);
         self.define(name);
 
+        // Static methods have no instance, so neither `this` nor `super` is in scope
+        self.current_class = ClassType::None;
+        for (kind, m) in methods {
+            if let (MethodKind::Static, Stmt::Function(_name, params, body, _)) = (kind, m) {
+                self.resolve_function(params, body, FunctionType::Function);
+            }
+        }
+        self.current_class = ClassType::Class;
+
         if let Some(super_class) = super_class {
-            if let Some(base_name) = name.lexeme.as_ref() {
-                if let Expr::Variable(super_name) = super_class {
-                    if let Some(super_name) = super_name.lexeme.as_ref() {
-                        if super_name.eq(base_name.as_str()) {
-                            return Err(ResolverError::ExistingVariable);
-                        }
-                    }
+            if let Expr::Variable(super_name, _) = super_class {
+                if super_name.lexeme == name.lexeme {
+                    self.error(ResolverError::InheritsFromItself(super_name.clone()));
                 }
             }
             self.current_class = ClassType::SubClass;
-            self.resolve_expr(super_class)?;
+            self.resolve_expr(super_class);
 
             self.begin_scope();
-            self.scopes
-                .last_mut()
-                .unwrap()
-                .insert("super".to_string(), true);
+            self.declare_implicit("super");
         }
 
         self.begin_scope();
-        self.scopes
-            .last_mut()
-            .unwrap()
-            .insert("this".to_string(), true);
-        for m in methods {
-            if let Stmt::Function(name, params, body) = m {
+        self.declare_implicit("this");
+        for (kind, m) in methods {
+            if let Stmt::Function(name, params, body, _) = m {
+                if *kind == MethodKind::Static {
+                    continue;
+                }
                 let mut decl = FunctionType::Method;
                 if let Some(name) = &name.lexeme {
-                    if name == "init" {
+                    if name == "init" && *kind == MethodKind::Method {
                         decl = FunctionType::Init;
                     }
                 }
-                self.resolve_function(params, body, decl)?;
+                self.resolve_function(params, body, decl);
             }
         }
         self.end_scope();
@@ -195,116 +491,150 @@ impl Resolver {
         }
 
         self.current_class = enclosing_class;
-        Ok(())
     }
 
-    fn super_expr(&self, keyword: &Token, expr: &Expr) -> ResolverResult<()> {
+    fn super_expr(&mut self, keyword: &Token, id: NodeId) {
         match self.current_class {
             ClassType::Class | ClassType::None => {
-                return Err(ResolverError::InvalidSuperStatement);
+                self.error(ResolverError::InvalidSuperStatement(keyword.clone()));
             }
             ClassType::SubClass => {}
         };
 
-        self.resolve_local(expr, keyword)?;
-        Ok(())
+        self.resolve_local(id, keyword, true);
     }
 
-    fn block(&mut self, body: &[Stmt]) -> ResolverResult<()> {
+    fn block(&mut self, body: &[Stmt]) {
         self.begin_scope();
-        self.resolve_stmts(body)?;
+        self.resolve_body(body);
         self.end_scope();
-        Ok(())
     }
 
-    fn var_stmt(&mut self, name: &Token, init: &Option<Expr>) -> ResolverResult<()> {
-        self.declare(name)?;
+    fn var_stmt(&mut self, name: &Token, init: &Option<Expr>) {
+        self.declare(name, true);
         if let Some(init) = init {
-            self.resolve_expr(init)?;
+            if self.scopes.is_empty() {
+                self.initializing = name.lexeme.clone();
+            }
+            self.resolve_expr(init);
+            self.initializing = None;
         }
         self.define(name);
-        Ok(())
     }
 
-    fn var_expr(&self, expr: &Expr, name: &Token) -> ResolverResult<()> {
-        if !self.scopes.is_empty() {
-            if let Some(last) = self.scopes.last() {
-                if let Some(res) = last.get(&name.lexeme.clone().unwrap()) {
-                    if !(*res) {
-                        return Err(ResolverError::UndefinedVariable);
-                    }
+    fn var_expr(&mut self, id: NodeId, name: &Token) {
+        if let Some(last) = self.scopes.last() {
+            if let Some(local) = last.get(lexeme(name)) {
+                if !local.defined {
+                    self.error(ResolverError::UndefinedVariable(name.clone()));
                 }
             }
+        } else if self.initializing.as_deref() == Some(lexeme(name)) {
+            // Reads whatever global of that name existed before, or fails at runtime
+            self.warnings
+                .push(ResolverWarning::SelfReference(name.clone()));
         }
-        self.resolve_local(expr, name)?;
-        Ok(())
+        self.resolve_local(id, name, true);
     }
 
-    fn assign_expr(&self, expr: &Expr, name: &Token, init: &Expr) -> ResolverResult<()> {
-        self.resolve_expr(init)?;
-        self.resolve_local(expr, name)?;
-        Ok(())
+    fn assign_expr(&mut self, id: NodeId, name: &Token, init: &Expr) {
+        self.resolve_expr(init);
+        self.resolve_local(id, name, false);
     }
 
-    fn function_stmt(
-        &mut self,
-        name: &Token,
-        params: &[Token],
-        body: &[Stmt],
-    ) -> ResolverResult<()> {
-        self.declare(name)?;
+    fn function_stmt(&mut self, name: &Token, params: &[Token], body: &[Stmt]) {
+        self.declare(name, true);
         self.define(name);
-        self.resolve_function(params, body, FunctionType::Function)?;
-        Ok(())
+        self.resolve_function(params, body, FunctionType::Function);
     }
 
-    fn resolve_function(
-        &mut self,
-        params: &[Token],
-        body: &[Stmt],
-        f_type: FunctionType,
-    ) -> ResolverResult<()> {
-        let enclosing_function = self.current_function.clone();
-        self.current_function = f_type;
+    // A lambda has its own `this` scope, since it can be stored on a class and called as
+    // a method
+    fn lambda(&mut self, params: &[Token], body: &[Stmt]) {
+        let enclosing_class = self.current_class.clone();
+        if let ClassType::None = self.current_class {
+            self.current_class = ClassType::Class;
+        }
+        self.begin_scope();
+        self.declare_implicit("this");
+        self.resolve_function(params, body, FunctionType::Function);
+        self.end_scope();
+        self.current_class = enclosing_class;
+    }
+
+    fn resolve_function(&mut self, params: &[Token], body: &[Stmt], f_type: FunctionType) {
+        let enclosing_function = mem::replace(&mut self.current_function, f_type);
+        // Loops do not extend into function bodies
+        let enclosing_loop_depth = mem::replace(&mut self.loop_depth, 0);
         self.begin_scope();
         for p in params {
-            self.declare(p)?;
+            self.declare(p, false);
             self.define(p);
         }
-        self.resolve_stmts(body)?;
+        self.resolve_body(body);
         self.end_scope();
+        self.loop_depth = enclosing_loop_depth;
         self.current_function = enclosing_function;
-        Ok(())
     }
 
-    fn if_stmt(
-        &mut self,
-        condition: &Expr,
-        consequent: &Stmt,
-        alternate: &Option<Box<Stmt>>,
-    ) -> ResolverResult<()> {
-        self.resolve_expr(condition)?;
-        self.resolve_stmt(consequent)?;
+    fn if_stmt(&mut self, condition: &Expr, consequent: &Stmt, alternate: &Option<Box<Stmt>>) {
+        self.resolve_expr(condition);
+        self.resolve_stmt(consequent);
         if let Some(alt) = alternate {
-            self.resolve_stmt(alt)?;
+            self.resolve_stmt(alt);
         }
-        Ok(())
     }
 
     // UTILS
-    fn declare(&mut self, name: &Token) -> ResolverResult<()> {
+    fn error(&mut self, error: ResolverError) {
+        self.errors.push(error);
+    }
+
+    // Parameters, loop and catch variables are exempt from the unused warning
+    fn declare(&mut self, name: &Token, warn_unused: bool) {
         if self.scopes.is_empty() {
-            return Ok(());
+            return;
         }
 
-        let scope = self.scopes.last_mut().unwrap();
         if let Some(lexeme) = &name.lexeme {
+            let (scope, enclosing) = self.scopes.split_last_mut().unwrap();
             if scope.contains_key(lexeme) {
-                return Err(ResolverError::ExistingVariable);
+                self.errors
+                    .push(ResolverError::ExistingVariable(name.clone()));
+                return;
             }
-            scope.insert(lexeme.to_string(), false);
+            if enclosing.iter().any(|s| s.contains_key(lexeme)) {
+                self.warnings
+                    .push(ResolverWarning::ShadowedVariable(name.clone()));
+            }
+            // Names starting with an underscore are deliberately unused
+            let checked = warn_unused && !lexeme.starts_with('_');
+            let slot = scope.len();
+            scope.insert(
+                lexeme.to_string(),
+                Local {
+                    defined: false,
+                    slot,
+                    read: false,
+                    declaration: if checked { Some(name.clone()) } else { None },
+                },
+            );
         }
-        Ok(())
+    }
+
+    // Declares a name the interpreter binds itself, such as `this`
+    fn declare_implicit(&mut self, name: &str) {
+        let scope = self.scopes.last_mut().unwrap();
+        let slot = scope.len();
+        scope.insert(
+            name.to_string(),
+            Local {
+                defined: true,
+                slot,
+                read: false,
+                declaration: None,
+            },
+        );
     }
 
     fn define(&mut self, name: &Token) {
@@ -313,8 +643,8 @@ impl Resolver {
         }
 
         if let Some(top) = self.scopes.last_mut() {
-            if let Some(lexeme) = &name.lexeme {
-                top.insert(lexeme.to_string(), true);
+            if let Some(local) = name.lexeme.as_ref().and_then(|n| top.get_mut(n)) {
+                local.defined = true;
             }
         }
     }
@@ -324,6 +654,23 @@ impl Resolver {
     }
 
     fn end_scope(&mut self) {
-        self.scopes.pop();
+        let Some(scope) = self.scopes.pop() else {
+            return;
+        };
+        let mut unused: Vec<Local> = scope
+            .into_values()
+            .filter(|local| !local.read && local.declaration.is_some())
+            .collect();
+        // Report in declaration order
+        unused.sort_by_key(|local| local.slot);
+        for local in unused {
+            if let Some(name) = local.declaration {
+                self.warnings.push(ResolverWarning::UnusedVariable(name));
+            }
+        }
     }
 }
+
+fn lexeme(token: &Token) -> &str {
+    token.lexeme.as_deref().unwrap_or_default()
+}
@@ -2,29 +2,46 @@ use std::fs;
 use std::io;
 use std::io::prelude::*;
 
-use vm::vm::Vm;
+use crate::exit_code;
+use vm::vm::{InterpreterError, Vm};
 
-pub fn repl(virtual_machine: &mut Vm) {
+pub fn repl(virtual_machine: &mut Vm) -> i32 {
     let mut input = String::new();
     let stdin = io::stdin();
 
     loop {
         print!("clox>");
         io::stdout().flush().expect("[ICE] Unable to flush stdout");
-        stdin.lock().read_line(&mut input).unwrap();
-        match virtual_machine.interpret(input.as_str()) {
-            Ok(()) => {}
-            Err(e) => panic!("Error: {:?}", e),
+        match stdin.lock().read_line(&mut input) {
+            // EOF
+            Ok(0) => return exit_code::OK,
+            Ok(_) => {}
+            Err(_) => return exit_code::IO_ERR,
+        }
+        if let Err(e) = virtual_machine.interpret(input.as_str()) {
+            println!("Error: {:?}", e);
         }
         input.clear();
     }
 }
 
-pub fn run_file(source_file: &str, virtual_machine: &mut Vm) {
-    let source = fs::read_to_string(source_file).expect("[ICE] Unable to read file");
+pub fn run_file(source_file: &str, virtual_machine: &mut Vm) -> i32 {
+    let source = match fs::read_to_string(source_file) {
+        Ok(source) => source,
+        Err(err) => {
+            println!("Unable to read file {}: {}", source_file, err);
+            return exit_code::NO_INPUT;
+        }
+    };
     // run
     match virtual_machine.interpret(source.as_str()) {
-        Ok(()) => {}
-        Err(e) => panic!("Error: {:?}", e),
+        Ok(()) => exit_code::OK,
+        Err(e) => {
+            println!("Error: {:?}", e);
+            match e {
+                InterpreterError::Compile(_) => exit_code::DATA_ERR,
+                InterpreterError::Runtime(_) => exit_code::SOFTWARE,
+            }
+        }
     }
 }
@@ -0,0 +1,7 @@
+// Exit codes follow the sysexits.h convention used by the reference implementation
+pub const OK: i32 = 0;
+pub const USAGE: i32 = 64;
+pub const DATA_ERR: i32 = 65;
+pub const NO_INPUT: i32 = 66;
+pub const SOFTWARE: i32 = 70;
+pub const IO_ERR: i32 = 74;
@@ -1,20 +1,633 @@
+use crate::exit_code;
 use crate::lox::Lox;
 use frontend::environment::Environment;
+use frontend::literal::Literal;
+use frontend::parser::Parser;
+use frontend::scanner::Scanner;
+use interpreter::checker::TypeChecker;
 use interpreter::interpreter::Interpreter;
+use interpreter::optimizer::{Optimizer, OptimizerStats};
+use interpreter::resolver::{Resolver, ResolverError};
 use std::cell::RefCell;
-use std::env;
 use std::path;
 use std::rc::Rc;
+use std::{env, fs};
 
-#[test]
-fn run_file() {
-    let mut lox = Lox::new();
+fn run_fixture(name: &str) -> i32 {
+    run_fixture_with_interpreter(name).0
+}
+
+fn run_fixture_with_interpreter(name: &str) -> (i32, Rc<RefCell<Interpreter>>) {
     let env = Environment::new(None);
-    let interpreter = Rc::new(RefCell::new(Interpreter::new(env)));
+    run_fixture_in(name, Interpreter::new(env))
+}
+
+fn run_fixture_in(name: &str, interpreter: Interpreter) -> (i32, Rc<RefCell<Interpreter>>) {
+    let mut lox = Lox::new();
+    let interpreter = Rc::new(RefCell::new(interpreter));
 
     let curr_dir = env::current_dir().expect("path");
     let mut file_path = path::PathBuf::new();
     file_path.push(curr_dir);
-    file_path.push("../__fixtures__/scope.lox");
-    lox.run_file(file_path, interpreter);
+    file_path.push("../__fixtures__");
+    file_path.push(name);
+    let code = lox.run_file(file_path, Rc::clone(&interpreter));
+    (code, interpreter)
+}
+
+fn check_fixture(name: &str) -> i32 {
+    let mut file_path = env::current_dir().expect("path");
+    file_path.push("../__fixtures__");
+    file_path.push(name);
+    Lox::new().check_file(file_path)
+}
+
+fn global(interpreter: &Rc<RefCell<Interpreter>>, name: &str) -> Option<Literal> {
+    interpreter.borrow().globals.borrow().get(name)
+}
+
+// Compares the printed value of each named global
+fn assert_globals(interpreter: &Rc<RefCell<Interpreter>>, expected: &[(&str, &str)]) {
+    for (name, value) in expected {
+        let actual = global(interpreter, name).expect("global should be defined");
+        assert_eq!(&actual.to_string(), value, "global '{}'", name);
+    }
+}
+
+#[test]
+fn run_file() {
+    assert_eq!(run_fixture("scope.lox"), exit_code::OK);
+}
+
+#[test]
+fn compile_errors_exit_with_data_err() {
+    assert_eq!(run_fixture("scanner-error.lox"), exit_code::DATA_ERR);
+    assert_eq!(run_fixture("parser-error.lox"), exit_code::DATA_ERR);
+    assert_eq!(run_fixture("invalid-this.lox"), exit_code::DATA_ERR);
+}
+
+#[test]
+fn runtime_errors_exit_with_software() {
+    assert_eq!(run_fixture("runtime-error.lox"), exit_code::SOFTWARE);
+    assert_eq!(run_fixture("super-missing.lox"), exit_code::SOFTWARE);
+}
+
+#[test]
+fn missing_file_exits_with_no_input() {
+    assert_eq!(run_fixture("does-not-exist.lox"), exit_code::NO_INPUT);
+}
+
+#[test]
+fn string_escapes_and_interpolation() {
+    let (code, interpreter) = run_fixture_with_interpreter("strings.lox");
+    assert_eq!(code, exit_code::OK);
+    assert_eq!(
+        global(&interpreter, "greeting"),
+        Some(Literal::String(
+            "Hello world!\tYou have 4 \"messages\"".to_string()
+        ))
+    );
+    assert_eq!(
+        global(&interpreter, "nested"),
+        Some(Literal::String("outer inner world".to_string()))
+    );
+    for name in &["appended", "prepended"] {
+        assert_eq!(
+            global(&interpreter, name),
+            Some(Literal::String("Unable to coerce into number".to_string()))
+        );
+    }
+}
+
+#[test]
+fn integer_arithmetic() {
+    let (code, interpreter) = run_fixture_with_interpreter("integers.lox");
+    assert_eq!(code, exit_code::OK);
+    // Compared through Debug, as integers and whole floats print the same
+    let expected = [
+        ("sum", "Integer(3)"),
+        ("promoted", "Number(1.5)"),
+        ("quotient", "Number(3.5)"),
+        ("truncated", "Integer(3)"),
+        ("remainder", "Integer(-1)"),
+        ("bits", "Integer(31)"),
+        ("big", "Integer(9007199254740993)"),
+    ];
+    for (name, value) in expected.iter() {
+        let actual = global(&interpreter, name).expect("global should be defined");
+        assert_eq!(&format!("{:?}", actual), value);
+    }
+}
+
+#[test]
+fn integer_overflow_is_a_runtime_error() {
+    assert_eq!(run_fixture("integer-overflow.lox"), exit_code::SOFTWARE);
+}
+
+#[test]
+fn break_and_continue() {
+    let (code, interpreter) = run_fixture_with_interpreter("break-continue.lox");
+    assert_eq!(code, exit_code::OK);
+    assert_eq!(global(&interpreter, "evens"), Some(Literal::Integer(20)));
+    assert_eq!(global(&interpreter, "count"), Some(Literal::Integer(5)));
+    assert_eq!(global(&interpreter, "pairs"), Some(Literal::Integer(3)));
+}
+
+#[test]
+fn break_outside_loop_is_a_compile_error() {
+    assert_eq!(run_fixture("invalid-break.lox"), exit_code::DATA_ERR);
+}
+
+#[test]
+fn lists() {
+    let (code, interpreter) = run_fixture_with_interpreter("lists.lox");
+    assert_eq!(code, exit_code::OK);
+    assert_globals(
+        &interpreter,
+        &[
+            ("numbers", "[10, 5, 3]"),
+            ("popped", "4"),
+            ("removed", "2"),
+            ("length", "3"),
+            ("middle", "[5, 3]"),
+            ("first", "10"),
+            ("nested", "[[1], []]"),
+            ("same", "true"),
+            ("cyclic", "[1, [...]]"),
+            ("cyclic_same", "true"),
+            ("cyclic_copy", "false"),
+        ],
+    );
+}
+
+#[test]
+fn list_index_out_of_bounds_is_a_runtime_error() {
+    assert_eq!(run_fixture("list-index-error.lox"), exit_code::SOFTWARE);
+    assert_eq!(run_fixture("list-slice-error.lox"), exit_code::SOFTWARE);
+}
+
+#[test]
+fn maps() {
+    let (code, interpreter) = run_fixture_with_interpreter("maps.lox");
+    assert_eq!(code, exit_code::OK);
+    assert_globals(
+        &interpreter,
+        &[
+            ("version", "2"),
+            ("truthy", "yes"),
+            ("same_key", "float key"),
+            ("has_debug", "true"),
+            ("deleted", "false"),
+            ("missing", "Nil"),
+            ("keys", "[name, version, true, Nil, 1]"),
+            ("values", "[1]"),
+            ("size", "5"),
+            ("empty", "{}"),
+            ("equal", "true"),
+            ("cyclic", "{name: cyclic, self: {...}}"),
+            ("cyclic_same", "true"),
+            ("cyclic_copy", "false"),
+        ],
+    );
+}
+
+#[test]
+fn invalid_map_access_is_a_runtime_error() {
+    assert_eq!(run_fixture("map-key-error.lox"), exit_code::SOFTWARE);
+    assert_eq!(run_fixture("map-missing-key.lox"), exit_code::SOFTWARE);
+    assert_eq!(run_fixture("map-nan-key.lox"), exit_code::SOFTWARE);
+}
+
+#[test]
+fn objects_compare_by_identity() {
+    let (code, interpreter) = run_fixture_with_interpreter("identity.lox");
+    assert_eq!(code, exit_code::OK);
+    assert_globals(
+        &interpreter,
+        &[
+            ("same", "true"),
+            ("twins", "false"),
+            ("through_this", "true"),
+            ("same_class", "true"),
+            ("classes_differ", "false"),
+            ("lookup", "first"),
+            ("by_class", "class"),
+            ("size", "3"),
+        ],
+    );
+}
+
+#[test]
+fn classes_are_shared_by_reference() {
+    let (code, interpreter) = run_fixture_with_interpreter("shared-classes.lox");
+    assert_eq!(code, exit_code::OK);
+    assert_globals(
+        &interpreter,
+        &[
+            ("created", "2"),
+            ("label", "counter"),
+            ("shadowed", "mine"),
+            ("unchanged", "counter"),
+            ("described", "created 2"),
+            ("spoken", "Rex makes a sound and barks"),
+            ("inherited", "animalia"),
+            ("renamed", "Max makes a sound and barks"),
+        ],
+    );
+}
+
+#[test]
+fn for_in_loops() {
+    let (code, interpreter) = run_fixture_with_interpreter("for-in.lox");
+    assert_eq!(code, exit_code::OK);
+    assert_globals(
+        &interpreter,
+        &[
+            ("total", "8"),
+            ("keys", "ab"),
+            ("reversed", "xol"),
+            ("counted", "[3, 2, 1]"),
+            ("bagged", "xy"),
+            ("captured", "3"),
+        ],
+    );
+}
+
+#[test]
+fn iterating_a_number_is_a_runtime_error() {
+    assert_eq!(run_fixture("not-iterable.lox"), exit_code::SOFTWARE);
+}
+
+#[test]
+fn lambdas() {
+    let (code, interpreter) = run_fixture_with_interpreter("lambdas.lox");
+    assert_eq!(code, exit_code::OK);
+    assert_globals(
+        &interpreter,
+        &[
+            ("doubled", "[2, 4, 6]"),
+            ("counted", "2"),
+            ("immediate", "3"),
+        ],
+    );
+}
+
+#[test]
+fn static_methods_getters_and_setters() {
+    let (code, interpreter) = run_fixture_with_interpreter("class-members.lox");
+    assert_eq!(code, exit_code::OK);
+    assert_globals(
+        &interpreter,
+        &[
+            ("squared", "9"),
+            ("diameter", "8"),
+            ("resized", "5"),
+            ("unit", "2"),
+            ("inherited", "1"),
+        ],
+    );
+}
+
+#[test]
+fn this_in_static_method_is_a_compile_error() {
+    assert_eq!(run_fixture("static-this.lox"), exit_code::DATA_ERR);
+}
+
+#[test]
+fn exceptions() {
+    let (code, interpreter) = run_fixture_with_interpreter("exceptions.lox");
+    assert_eq!(code, exit_code::OK);
+    assert_globals(
+        &interpreter,
+        &[
+            ("caught", "boom"),
+            ("message", "Division by zero"),
+            ("line", "12"),
+            ("custom", "custom"),
+            ("returned", "returned"),
+            ("steps", "try finally!"),
+            ("rethrown", "2"),
+            ("looped", "34"),
+        ],
+    );
+}
+
+#[test]
+fn uncaught_throw_is_a_runtime_error() {
+    assert_eq!(run_fixture("uncaught-throw.lox"), exit_code::SOFTWARE);
+}
+
+#[test]
+fn modules() {
+    let (code, interpreter) = run_fixture_with_interpreter("modules/main.lox");
+    assert_eq!(code, exit_code::OK);
+    assert_globals(
+        &interpreter,
+        &[
+            ("area", "9"),
+            ("shape", "box"),
+            ("tau", "6.28"),
+            // Both imports of counter.lox share one module instance
+            ("count", "2"),
+        ],
+    );
+}
+
+#[test]
+fn import_errors_are_runtime_errors() {
+    assert_eq!(run_fixture("modules/cycle-a.lox"), exit_code::SOFTWARE);
+    assert_eq!(run_fixture("modules/missing.lox"), exit_code::SOFTWARE);
+    // Only exported names are visible
+    assert_eq!(run_fixture("modules/private.lox"), exit_code::SOFTWARE);
+}
+
+#[test]
+fn conditional_and_comma_operators() {
+    let (code, interpreter) = run_fixture_with_interpreter("conditional-comma.lox");
+    assert_eq!(code, exit_code::OK);
+    assert_globals(
+        &interpreter,
+        &[
+            ("signs", "[positive, negative, zero]"),
+            ("last", "3"),
+            // Only the taken branch is evaluated
+            ("picked", "4"),
+            ("calls", "4"),
+            ("a", "3"),
+            ("b", "6"),
+        ],
+    );
+}
+
+#[test]
+fn compound_assignment_and_increments() {
+    let (code, interpreter) = run_fixture_with_interpreter("compound-assignment.lox");
+    assert_eq!(code, exit_code::OK);
+    assert_globals(
+        &interpreter,
+        &[
+            ("total", "2"),
+            ("greeting", "Hello world"),
+            ("post", "5"),
+            ("pre", "7"),
+            ("down", "7"),
+            ("i", "6"),
+            ("old", "11"),
+            ("calls", "3"),
+            ("list", "[101, 2, 4]"),
+            ("at", "1"),
+            ("bumped", "4"),
+        ],
+    );
+}
+
+#[test]
+fn match_statements() {
+    let (code, interpreter) = run_fixture_with_interpreter("match.lox");
+    assert_eq!(code, exit_code::OK);
+    assert_globals(
+        &interpreter,
+        &[
+            (
+                "described",
+                "[small, small, negative, letter, other, unknown]",
+            ),
+            ("kinds", "[circle, shape, none, none]"),
+            ("untouched", "yes"),
+            ("matched", "4"),
+        ],
+    );
+}
+
+#[test]
+fn invalid_match_patterns() {
+    assert_eq!(run_fixture("match-duplicate.lox"), exit_code::DATA_ERR);
+    assert_eq!(run_fixture("match-not-class.lox"), exit_code::SOFTWARE);
+}
+
+#[test]
+fn type_annotations_are_checked() {
+    assert_eq!(check_fixture("types.lox"), exit_code::OK);
+    assert_eq!(check_fixture("type-errors.lox"), exit_code::DATA_ERR);
+}
+
+#[test]
+fn unannotated_variables_are_inferred() {
+    let mut file_path = env::current_dir().expect("path");
+    file_path.push("../__fixtures__/inferred-types.lox");
+    let source = fs::read_to_string(&file_path).expect("fixture");
+    let tokens = Scanner::new(&source).scan_tokens().expect("scan");
+    let ast = Parser::new(tokens).parse().expect("parse");
+    let errors: Vec<String> = TypeChecker::new()
+        .check(&ast)
+        .expect_err("fixture has type errors")
+        .iter()
+        .map(|e| e.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            "Cannot initialize variable 'n' of type Number with String at line 7",
+            "Expected argument 1 to be Number but got String at line 8",
+        ]
+    );
+}
+
+#[test]
+fn type_annotations_are_ignored_at_runtime() {
+    let (code, interpreter) = run_fixture_with_interpreter("types.lox");
+    assert_eq!(code, exit_code::OK);
+    assert_globals(
+        &interpreter,
+        &[
+            ("description", "square of area 9"),
+            ("doubled", "18"),
+            ("same", "true"),
+        ],
+    );
+    // Mismatched annotations only fail the checker
+    assert_eq!(run_fixture("type-errors.lox"), exit_code::SOFTWARE);
+}
+
+#[test]
+fn locals_resolve_to_their_slots() {
+    let (code, interpreter) = run_fixture_with_interpreter("slots.lox");
+    assert_eq!(code, exit_code::OK);
+    assert_globals(
+        &interpreter,
+        &[
+            ("shadowed", "inner"),
+            ("sums", "112"),
+            ("counter", "4"),
+            ("made", "made!"),
+            ("greeted", "base!"),
+            ("reinitialised", "2"),
+        ],
+    );
+}
+
+#[test]
+fn debug_mode_cross_checks_resolution() {
+    let mut interpreter = Interpreter::new(Environment::new(None));
+    interpreter.debug = true;
+    let (code, interpreter) = run_fixture_in("slots.lox", interpreter);
+    assert_eq!(code, exit_code::OK);
+    let shadowed = global(&interpreter, "shadowed").expect("global should be defined");
+    assert_eq!(shadowed.to_string(), "inner");
+    assert!(interpreter.borrow().diagnostics.is_empty());
+
+    // A closure declared before a shadowing local reads the global, while a lookup by
+    // name would find the local
+    let mut interpreter = Interpreter::new(Environment::new(None));
+    interpreter.debug = true;
+    let (code, interpreter) = run_fixture_in("debug-shadowing.lox", interpreter);
+    assert_eq!(code, exit_code::OK);
+    let seen = global(&interpreter, "seen").expect("global should be defined");
+    assert_eq!(seen.to_string(), "global");
+    assert_eq!(
+        interpreter.borrow().diagnostics,
+        ["[line 5] Debug: 'a' resolved to a global but lookup by name finds the local 1 scope(s) out"]
+    );
+}
+
+fn resolve_fixture(name: &str) -> (Resolver, Result<(), Vec<ResolverError>>) {
+    let mut file_path = env::current_dir().expect("path");
+    file_path.push("../__fixtures__");
+    file_path.push(name);
+    let source = fs::read_to_string(&file_path).expect("fixture");
+    let tokens = Scanner::new(&source).scan_tokens().expect("scan");
+    let ast = Parser::new(tokens).parse().expect("parse");
+    let mut resolver = Resolver::new();
+    let res = resolver.resolve_stmts(&ast);
+    (resolver, res)
+}
+
+#[test]
+fn resolver_warnings() {
+    let (resolver, res) = resolve_fixture("warnings.lox");
+    assert!(res.is_ok());
+    let warnings: Vec<String> = resolver
+        .warnings()
+        .iter()
+        .map(|w| format!("{}: {}", w.token().line, w))
+        .collect();
+    assert_eq!(
+        warnings,
+        [
+            "2: Variable 'total' is read in its own initializer",
+            "7: Unreachable code after 'return'",
+            "5: Local variable 'unused' is never read",
+            "14: Variable 'outer' shadows a local of an enclosing scope",
+            "12: Local variable 'outer' is never read",
+        ]
+    );
+}
+
+#[test]
+fn denied_warnings_stop_the_script() {
+    assert_eq!(run_fixture("warnings.lox"), exit_code::OK);
+
+    let mut file_path = env::current_dir().expect("path");
+    file_path.push("../__fixtures__/warnings.lox");
+    let mut lox = Lox::new();
+    lox.deny_warnings = true;
+    let interpreter = Rc::new(RefCell::new(Interpreter::default()));
+    let code = lox.run_file(file_path, Rc::clone(&interpreter));
+    assert_eq!(code, exit_code::DATA_ERR);
+    assert!(global(&interpreter, "result").is_none());
+}
+
+#[test]
+fn denied_warnings_include_imported_modules() {
+    let (code, interpreter) = run_fixture_with_interpreter("modules/warnings.lox");
+    assert_eq!(code, exit_code::OK);
+    let value = global(&interpreter, "value").expect("global should be defined");
+    assert_eq!(value.to_string(), "42");
+
+    let mut file_path = env::current_dir().expect("path");
+    file_path.push("../__fixtures__/modules/warnings.lox");
+    let mut lox = Lox::new();
+    lox.deny_warnings = true;
+    let interpreter = Rc::new(RefCell::new(Interpreter::default()));
+    let code = lox.run_file(file_path, Rc::clone(&interpreter));
+    assert_eq!(code, exit_code::DATA_ERR);
+    // Nothing runs, not even the statements before the import
+    assert!(global(&interpreter, "before").is_none());
+    assert!(global(&interpreter, "value").is_none());
+}
+
+#[test]
+fn resolver_reports_every_error() {
+    let (_, res) = resolve_fixture("resolver-errors.lox");
+    let errors: Vec<String> = res
+        .expect_err("fixture has resolver errors")
+        .iter()
+        .map(|e| format!("{}:{}: {}", e.token().line, e.token().column, e))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            "2:13: Can't read local variable 'a' in its own initializer",
+            "4:9: Already a variable named 'b' in this scope",
+            "6:1: Can't return from top-level code",
+            "7:14: Class 'Loop' can't inherit from itself",
+            "9:5: Can't use 'break' outside of a loop",
+            "11:7: Can't use 'this' outside of a method",
+            "14:13: Pattern '1' is already matched by an earlier case",
+        ]
+    );
+    assert_eq!(run_fixture("resolver-errors.lox"), exit_code::DATA_ERR);
+}
+
+#[test]
+fn optimizer_preserves_results() {
+    let globals = [
+        ("sum", "7"),
+        ("ratio", "1.5"),
+        ("greeting", "Hello, world!"),
+        ("label", "answer: 42"),
+        ("negated", "-5"),
+        ("flag", "false"),
+        ("branch", "else"),
+        ("skipped", "true"),
+        ("iterations", "0"),
+        ("mixed", "10"),
+        ("message", "Division by zero"),
+        ("line", "30"),
+        ("chosen", "4"),
+        ("fallback", "default"),
+        ("shorted", "false"),
+        ("kept", "true"),
+    ];
+
+    let mut file_path = env::current_dir().expect("path");
+    file_path.push("../__fixtures__/optimizer.lox");
+    for &optimize in &[false, true] {
+        let mut lox = Lox::new();
+        lox.optimize = optimize;
+        let interpreter = Rc::new(RefCell::new(Interpreter::default()));
+        let code = lox.run_file(file_path.clone(), Rc::clone(&interpreter));
+        assert_eq!(code, exit_code::OK, "optimize={}", optimize);
+        assert_globals(&interpreter, &globals);
+    }
+}
+
+#[test]
+fn optimizer_folds_constants_and_dead_code() {
+    let mut file_path = env::current_dir().expect("path");
+    file_path.push("../__fixtures__/optimizer.lox");
+    let source = fs::read_to_string(&file_path).expect("fixture");
+    let tokens = Scanner::new(&source).scan_tokens().expect("scan");
+    let ast = Parser::new(tokens).parse().expect("parse");
+
+    let mut optimizer = Optimizer::new();
+    optimizer.optimize(ast);
+    assert_eq!(
+        optimizer.stats(),
+        OptimizerStats {
+            folded: 20,
+            removed_branches: 2,
+            removed_loops: 2,
+        }
+    );
 }
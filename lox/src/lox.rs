@@ -1,42 +1,99 @@
 use std::cell::RefCell;
 use std::io::prelude::*;
 use std::rc::Rc;
-use std::{fs, io, path, process};
+use std::{fs, io, path};
 
+use crate::exit_code;
+use frontend::ast::Stmt;
 use frontend::parser::Parser;
+use frontend::runnable::EarlyReturn;
 use frontend::scanner::Scanner;
-use frontend::token::Token;
+use interpreter::checker::TypeChecker;
 use interpreter::interpreter::Interpreter;
+use interpreter::optimizer::Optimizer;
 use interpreter::resolver::Resolver;
 
 pub struct Lox {
     error: Option<String>,
+    runtime_error: Option<String>,
+    // Treats resolver warnings as errors, so scripts with warnings are not run
+    pub deny_warnings: bool,
+    // Folds constants and drops dead code before running the script
+    pub optimize: bool,
+    // Reports what the optimizer changed
+    pub verbose: bool,
 }
 
 impl Lox {
     pub fn new() -> Self {
-        Lox { error: None }
+        Lox {
+            error: None,
+            runtime_error: None,
+            deny_warnings: false,
+            optimize: false,
+            verbose: false,
+        }
     }
 
-    pub fn run_file(&mut self, path: path::PathBuf, i: Rc<RefCell<Interpreter>>) {
-        let source = fs::read_to_string(path).expect("Unable to read file");
+    pub fn run_file(&mut self, path: path::PathBuf, i: Rc<RefCell<Interpreter>>) -> i32 {
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(err) => {
+                println!("Unable to read file {}: {}", path.display(), err);
+                return exit_code::NO_INPUT;
+            }
+        };
+        i.borrow_mut().modules.set_entry(&path);
         self.run(source.as_str(), i);
 
         if self.error.is_some() {
-            process::exit(1)
+            exit_code::DATA_ERR
+        } else if self.runtime_error.is_some() {
+            exit_code::SOFTWARE
+        } else {
+            exit_code::OK
+        }
+    }
+
+    // Type checks a file without running it
+    pub fn check_file(&mut self, path: path::PathBuf) -> i32 {
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(err) => {
+                println!("Unable to read file {}: {}", path.display(), err);
+                return exit_code::NO_INPUT;
+            }
+        };
+        let ast = match self.parse(source.as_str()) {
+            Some(ast) => ast,
+            None => return exit_code::DATA_ERR,
+        };
+        if let Err(errs) = TypeChecker::new().check(&ast) {
+            for err in errs {
+                println!("[line {}] Type error: {}", err.line, err.message);
+                self.error = Some(err.message);
+            }
+            return exit_code::DATA_ERR;
         }
+        exit_code::OK
     }
 
-    pub fn run_prompt(&mut self, i: Rc<RefCell<Interpreter>>) {
+    pub fn run_prompt(&mut self, i: Rc<RefCell<Interpreter>>) -> i32 {
         let mut input = String::new();
         let stdin = io::stdin();
         loop {
             print!("lox> ");
             io::stdout().flush().expect("[ICE] Unable to flush stdout");
-            stdin.lock().read_line(&mut input).unwrap();
+            match stdin.lock().read_line(&mut input) {
+                // EOF
+                Ok(0) => return exit_code::OK,
+                Ok(_) => {}
+                Err(_) => return exit_code::IO_ERR,
+            }
             self.run(input.as_str(), Rc::clone(&i));
             input.clear();
             self.error = None;
+            self.runtime_error = None;
         }
     }
 
@@ -45,30 +102,119 @@ impl Lox {
         self.error = Some(message);
     }
 
-    fn run(&mut self, source: &str, interpreter: Rc<RefCell<Interpreter>>) {
+    fn report_runtime(&mut self, err: EarlyReturn) {
+        let message = match err {
+            EarlyReturn::Error(e) => format!("{}", e),
+            EarlyReturn::Throw(value) => format!("Uncaught exception: {}", value),
+            EarlyReturn::Return(_) => "Can't return from top-level code".to_string(),
+            EarlyReturn::Break | EarlyReturn::Continue => {
+                "Can't break or continue outside of a loop".to_string()
+            }
+        };
+        println!("Runtime Error: {}", message);
+        self.runtime_error = Some(message);
+    }
+
+    // Scans and parses the source, reporting any errors
+    fn parse(&mut self, source: &str) -> Option<Vec<Stmt>> {
         // Lexer
         let mut scanner = Scanner::new(source);
-        let mut tokens: Vec<Token> = Vec::new();
-        match scanner.scan_tokens() {
-            Ok(ts) => tokens = ts,
-            Err(err) => self.report(err.line(), format!("{}", err)),
-        }
+        let tokens = match scanner.scan_tokens() {
+            Ok(ts) => ts,
+            Err(err) => {
+                self.report(err.line(), format!("{}", err));
+                return None;
+            }
+        };
 
         // Parser
         let mut parser = Parser::new(tokens);
-        let ast = parser.parse().expect("Parser Error");
+        match parser.parse() {
+            Ok(ast) => Some(ast),
+            Err(errs) => {
+                for err in errs {
+                    self.report(err.line(), format!("{}", err));
+                }
+                None
+            }
+        }
+    }
+
+    // Returns whether any module had warnings
+    fn report_module_warnings(&self, interpreter: &Rc<RefCell<Interpreter>>) -> bool {
+        let warnings = interpreter.borrow_mut().modules.take_warnings();
+        for (path, warning) in &warnings {
+            let token = warning.token();
+            println!(
+                "[{}, line {}, column {}] Warning: {}",
+                path.display(),
+                token.line,
+                token.column,
+                warning
+            );
+        }
+        !warnings.is_empty()
+    }
 
-        let mut resolver = Resolver::new(Rc::clone(&interpreter));
-        let resolver_res = resolver.resolve_stmts(&ast);
+    fn run(&mut self, source: &str, interpreter: Rc<RefCell<Interpreter>>) {
+        let mut ast = match self.parse(source) {
+            Some(ast) => ast,
+            None => return,
+        };
 
-        if resolver_res.is_err() {
-            panic!("Error from resolver");
+        let mut resolver = Resolver::new();
+        let res = resolver.resolve_stmts(&ast);
+        for warning in resolver.warnings() {
+            let token = warning.token();
+            println!(
+                "[line {}, column {}] Warning: {}",
+                token.line, token.column, warning
+            );
+        }
+        if let Err(errs) = res {
+            for err in errs {
+                let token = err.token();
+                println!(
+                    "[line {}, column {}] Error: {}",
+                    token.line, token.column, err
+                );
+                self.error = Some(err.to_string());
+            }
+            return;
         }
 
-        // Interpreter
+        // Imported modules are checked before anything runs, so their warnings are denied
+        // like the program's own
+        interpreter.borrow_mut().modules.deny_warnings = self.deny_warnings;
         interpreter
             .borrow_mut()
-            .interpret(ast)
-            .expect("Interpreter Error");
+            .modules
+            .prepare(resolver.take_imports());
+        let module_warned = self.report_module_warnings(&interpreter);
+        if self.deny_warnings && (module_warned || !resolver.warnings().is_empty()) {
+            self.error = Some("Warnings are denied".to_string());
+            return;
+        }
+        interpreter.borrow_mut().resolve(resolver.into_locals());
+
+        if self.optimize {
+            let mut optimizer = Optimizer::new();
+            ast = optimizer.optimize(ast);
+            if self.verbose {
+                let stats = optimizer.stats();
+                println!(
+                    "Optimizer: folded {} expression(s), removed {} branch(es) and {} loop(s)",
+                    stats.folded, stats.removed_branches, stats.removed_loops
+                );
+            }
+        }
+
+        // Interpreter
+        let res = interpreter.borrow_mut().interpret(&ast);
+        // Modules that could not be checked up front are resolved as they are loaded
+        self.report_module_warnings(&interpreter);
+        if let Err(err) = res {
+            self.report_runtime(err);
+        }
     }
 }
@@ -3,6 +3,7 @@ extern crate interpreter;
 extern crate vm;
 
 mod clox;
+mod exit_code;
 mod lox;
 
 #[cfg(test)]
@@ -12,40 +13,82 @@ use frontend::{environment::Environment, literal::Literal};
 use interpreter::{clock::Clock, interpreter::Interpreter};
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::{env, path};
+use std::{env, path, process};
 use vm::chunk::Chunk;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
+
+    let code = match args.get(1).map(String::as_str) {
+        Some("jlox") => jlox(&args[2..]),
+        Some("clox") => clox(&args[2..]),
+        Some("check") => check(&args[2..]),
+        _ => {
+            println!("usage: lox [jlox|clox|check] [filename.lox]");
+            exit_code::USAGE
+        }
+    };
+    process::exit(code);
+}
+
+fn jlox(args: &[String]) -> i32 {
+    let (flags, args): (Vec<&String>, Vec<&String>) =
+        args.iter().partition(|arg| arg.starts_with("--"));
     let mut lox = lox::Lox::new();
+    let env = Environment::new(None);
+    let interpreter = Rc::new(RefCell::new(Interpreter::new(env)));
+
+    for flag in flags {
+        match flag.as_str() {
+            "--debug" => interpreter.borrow_mut().debug = true,
+            "--deny-warnings" => lox.deny_warnings = true,
+            "--opt" => lox.optimize = true,
+            "--verbose" => lox.verbose = true,
+            _ => {
+                println!(
+                    "usage: jlox [--debug] [--deny-warnings] [--opt] [--verbose] [filename.lox]"
+                );
+                return exit_code::USAGE;
+            }
+        }
+    }
+
+    // Add clock function to global env
+    interpreter
+        .borrow()
+        .environment
+        .borrow_mut()
+        .define("clock".to_string(), Literal::Callable(Box::new(Clock {})));
 
-    if args[1] == "jlox" {
-        let env = Environment::new(None);
-        let interpreter = Rc::new(RefCell::new(Interpreter::new(env)));
-
-        // Add clock function to global env
-        interpreter
-            .borrow()
-            .environment
-            .borrow_mut()
-            .define("clock".to_string(), Literal::Callable(Box::new(Clock {})));
-
-        if args.len() == 2 {
-            lox.run_prompt(interpreter);
-        } else if args.len() == 3 {
-            lox.run_file(path::PathBuf::from(&args[2]), interpreter);
-        } else {
-            println!("usage: jlox [filename.lox]")
+    match args.as_slice() {
+        [] => lox.run_prompt(interpreter),
+        [file] => lox.run_file(path::PathBuf::from(file), interpreter),
+        _ => {
+            println!("usage: jlox [--debug] [--deny-warnings] [--opt] [--verbose] [filename.lox]");
+            exit_code::USAGE
         }
-    } else if args[1] == "clox" {
-        let chunk = Chunk::new();
-        let mut virtual_machine = vm::vm::Vm::new(chunk);
-        if args.len() == 2 {
-            clox::repl(&mut virtual_machine);
-        } else if args.len() == 3 {
-            clox::run_file(args[2].as_str(), &mut virtual_machine);
-        } else {
+    }
+}
+
+fn check(args: &[String]) -> i32 {
+    match args {
+        [file] => lox::Lox::new().check_file(path::PathBuf::from(file)),
+        _ => {
+            println!("usage: lox check [filename.lox]");
+            exit_code::USAGE
+        }
+    }
+}
+
+fn clox(args: &[String]) -> i32 {
+    let chunk = Chunk::new();
+    let mut virtual_machine = vm::vm::Vm::new(chunk);
+    match args {
+        [] => clox::repl(&mut virtual_machine),
+        [file] => clox::run_file(file.as_str(), &mut virtual_machine),
+        _ => {
             println!("Usage: clox [path]");
+            exit_code::USAGE
         }
     }
 }
@@ -10,6 +10,9 @@ pub enum Error {
 pub enum ScannerError {
     UnknownCharacter(char, usize),
     UntermiantedString(usize),
+    UnterminatedComment(usize),
+    InvalidEscape(String, usize),
+    MalformedNumber(String, String, usize),
     InvalidCharacter(char, usize),
     InvalidTerm(String, usize),
     UnknownError,
@@ -20,26 +23,53 @@ pub enum InterpreterError {
     InvalidCoercion(String),
     InvalidAstType,
     UndefinedVariable(String),
+    // A resolved local missing from the scope it was resolved to, with its name,
+    // scope distance and line
+    UnresolvedLocal(String, usize, usize),
     MismatchFunctionArity,
+    IntegerOverflow,
+    DivisionByZero,
+    IndexOutOfBounds(i64, usize),
+    // Start and end of a slice whose start comes after its end
+    InvalidSlice(usize, usize),
+    UndefinedProperty(String),
+    UndefinedKey(String),
+    UnhashableKey(String),
+    NotIterable(String),
+    ModuleNotFound(String),
+    ImportCycle(String),
+    InvalidModule(String, String),
+    NotAClass(String),
 }
 
 #[derive(Debug)]
 pub enum ParserError {
     UnexpectedToken(String, usize),
     GenericError(String, usize),
-    InvalidAssignmentTarget,
-    ArgumentCountExceeded,
+    InvalidAssignmentTarget(usize),
+    ArgumentCountExceeded(usize),
 }
 
 impl fmt::Display for ScannerError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match &*self {
+        match self {
             ScannerError::UnknownCharacter(c, line_number) => {
                 write!(f, "Unrecognised character {} at line {}", c, line_number)
             }
             ScannerError::UntermiantedString(line_number) => {
                 write!(f, "Unterminated string at line {}", line_number)
             }
+            ScannerError::UnterminatedComment(line_number) => {
+                write!(f, "Unterminated block comment at line {}", line_number)
+            }
+            ScannerError::InvalidEscape(s, line_number) => {
+                write!(f, "Invalid escape sequence {} at line {}", s, line_number)
+            }
+            ScannerError::MalformedNumber(s, reason, line_number) => write!(
+                f,
+                "Malformed number literal '{}': {} at line {}",
+                s, reason, line_number
+            ),
             ScannerError::InvalidCharacter(c, line_number) => {
                 write!(f, "Invalid character {} at line {}", c, line_number)
             }
@@ -56,9 +86,96 @@ impl ScannerError {
         match *self {
             ScannerError::UnknownCharacter(_, line_number) => line_number,
             ScannerError::UntermiantedString(line_number) => line_number,
+            ScannerError::UnterminatedComment(line_number) => line_number,
+            ScannerError::InvalidEscape(_, line_number) => line_number,
+            ScannerError::MalformedNumber(_, _, line_number) => line_number,
             ScannerError::InvalidCharacter(_, line_number) => line_number,
             ScannerError::InvalidTerm(_, line_number) => line_number,
             ScannerError::UnknownError => 0,
         }
     }
 }
+
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParserError::UnexpectedToken(msg, line_number) => {
+                write!(f, "{} at line {}", msg, line_number)
+            }
+            ParserError::GenericError(msg, line_number) => {
+                write!(f, "{} at line {}", msg, line_number)
+            }
+            ParserError::InvalidAssignmentTarget(line_number) => {
+                write!(f, "Invalid assignment target at line {}", line_number)
+            }
+            ParserError::ArgumentCountExceeded(line_number) => {
+                write!(
+                    f,
+                    "Can't have more than 255 arguments at line {}",
+                    line_number
+                )
+            }
+        }
+    }
+}
+
+impl ParserError {
+    pub fn line(&self) -> usize {
+        match *self {
+            ParserError::UnexpectedToken(_, line_number) => line_number,
+            ParserError::GenericError(_, line_number) => line_number,
+            ParserError::InvalidAssignmentTarget(line_number) => line_number,
+            ParserError::ArgumentCountExceeded(line_number) => line_number,
+        }
+    }
+}
+
+impl fmt::Display for InterpreterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InterpreterError::InvalidCoercion(msg) => write!(f, "{}", msg),
+            InterpreterError::InvalidAstType => write!(f, "Invalid operand type"),
+            InterpreterError::UndefinedVariable(name) => {
+                write!(f, "Undefined variable '{}'", name)
+            }
+            InterpreterError::UnresolvedLocal(name, distance, line) => write!(
+                f,
+                "Local variable '{}' not found {} scope(s) out at line {}",
+                name, distance, line
+            ),
+            InterpreterError::MismatchFunctionArity => {
+                write!(f, "Mismatched number of arguments")
+            }
+            InterpreterError::IntegerOverflow => write!(f, "Integer overflow"),
+            InterpreterError::DivisionByZero => write!(f, "Division by zero"),
+            InterpreterError::IndexOutOfBounds(index, len) => {
+                write!(
+                    f,
+                    "Index {} out of bounds for list of length {}",
+                    index, len
+                )
+            }
+            InterpreterError::InvalidSlice(start, end) => {
+                write!(f, "Slice start {} is after its end {}", start, end)
+            }
+            InterpreterError::UndefinedProperty(name) => {
+                write!(f, "Undefined property '{}'", name)
+            }
+            InterpreterError::ModuleNotFound(path) => write!(f, "Unable to find module '{}'", path),
+            InterpreterError::ImportCycle(chain) => write!(f, "Import cycle: {}", chain),
+            InterpreterError::InvalidModule(path, msg) => {
+                write!(f, "Unable to load module '{}': {}", path, msg)
+            }
+            InterpreterError::NotIterable(value) => write!(f, "'{}' is not iterable", value),
+            InterpreterError::NotAClass(value) => write!(f, "'{}' is not a class", value),
+            InterpreterError::UndefinedKey(key) => write!(f, "Undefined key '{}'", key),
+            InterpreterError::UnhashableKey(key) => {
+                write!(
+                    f,
+                    "Only strings, numbers other than NaN, booleans, nil, classes and instances can be map keys, got '{}'",
+                    key
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+// A number literal as written in the source, shared by the tree-walking and bytecode
+// scanners, which convert it to their own value type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Integer(i64),
+    Float(f64),
+}
+
+// Validates and converts a captured number literal such as 0xFF, 0b1010, 1e-9 or 1_000.
+// Literals without a fraction or exponent become integers.
+pub fn parse_number(lexeme: &str) -> Result<Number, &'static str> {
+    let radix_digits = |prefix: [&str; 2]| prefix.iter().find_map(|p| lexeme.strip_prefix(p));
+    if let Some(digits) = radix_digits(["0x", "0X"]) {
+        return parse_radix(digits, 16);
+    }
+    if let Some(digits) = radix_digits(["0b", "0B"]) {
+        return parse_radix(digits, 2);
+    }
+
+    let (mantissa, exponent) = match lexeme.find(['e', 'E']) {
+        Some(idx) => (&lexeme[..idx], Some(&lexeme[idx + 1..])),
+        None => (lexeme, None),
+    };
+    let (integer, fraction) = match mantissa.find('.') {
+        Some(idx) => (&mantissa[..idx], Some(&mantissa[idx + 1..])),
+        None => (mantissa, None),
+    };
+
+    check_digits(integer, 10)?;
+    if let Some(fraction) = fraction {
+        check_digits(fraction, 10).map_err(|_| "expected digits after decimal point")?;
+    }
+    if let Some(exponent) = exponent {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        check_digits(exponent, 10).map_err(|_| "expected digits in exponent")?;
+    }
+
+    // Decimal integers too large for an i64 are still valid numbers, as floats
+    if fraction.is_none() && exponent.is_none() {
+        if let Ok(number) = parse_radix(integer, 10) {
+            return Ok(number);
+        }
+    }
+    lexeme
+        .replace('_', "")
+        .parse::<f64>()
+        .map(Number::Float)
+        .map_err(|_| "invalid number")
+}
+
+fn parse_radix(digits: &str, radix: u32) -> Result<Number, &'static str> {
+    check_digits(digits, radix)?;
+    i64::from_str_radix(digits.replace('_', "").as_str(), radix)
+        .map(Number::Integer)
+        .map_err(|_| "literal is too large")
+}
+
+// Digit separators are only allowed between digits
+fn check_digits(digits: &str, radix: u32) -> Result<(), &'static str> {
+    if digits.is_empty() {
+        return Err("expected digits");
+    }
+    if digits.chars().any(|c| c != '_' && !c.is_digit(radix)) {
+        return Err("invalid digit");
+    }
+    if digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+        return Err("'_' must separate digits");
+    }
+    Ok(())
+}
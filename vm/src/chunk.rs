@@ -1,4 +1,4 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use crate::opcode::OpCode;
 
@@ -8,8 +8,34 @@ pub struct OpCodeLine {
     pub line: usize,
 }
 
+// Guards the instructions in `start..end`. When one of them throws, the stack is cut
+// back to `stack_depth` and execution resumes at `target` with the error on top.
+#[derive(Debug, Clone, Copy)]
+pub struct ExceptionHandler {
+    pub start: usize,
+    pub end: usize,
+    pub target: usize,
+    pub stack_depth: usize,
+}
+
+// Jump offsets of a match statement, keyed by the integer each case matches. Subjects
+// without a case take the default offset.
+#[derive(Debug, Clone, Default)]
+pub struct JumpTable {
+    pub cases: HashMap<i64, usize>,
+    pub default: usize,
+}
+
+impl JumpTable {
+    pub fn offset(&self, subject: i64) -> usize {
+        self.cases.get(&subject).copied().unwrap_or(self.default)
+    }
+}
+
 pub struct Chunk {
     pub code: Rc<RefCell<Vec<OpCodeLine>>>,
+    pub handlers: Rc<RefCell<Vec<ExceptionHandler>>>,
+    pub jump_tables: Rc<RefCell<Vec<JumpTable>>>,
 }
 
 impl Default for Chunk {
@@ -22,6 +48,8 @@ impl Clone for Chunk {
     fn clone(&self) -> Self {
         Chunk {
             code: Rc::clone(&self.code),
+            handlers: Rc::clone(&self.handlers),
+            jump_tables: Rc::clone(&self.jump_tables),
         }
     }
 }
@@ -30,6 +58,8 @@ impl Chunk {
     pub fn new() -> Self {
         Chunk {
             code: Rc::new(RefCell::new(Vec::new())),
+            handlers: Rc::new(RefCell::new(Vec::new())),
+            jump_tables: Rc::new(RefCell::new(Vec::new())),
         }
     }
 
@@ -39,4 +69,25 @@ impl Chunk {
             line,
         });
     }
+
+    pub fn add_handler(&mut self, handler: ExceptionHandler) {
+        self.handlers.borrow_mut().push(handler);
+    }
+
+    // Returns the index to use with OpCode::JumpTable
+    pub fn add_jump_table(&mut self, table: JumpTable) -> usize {
+        let mut tables = self.jump_tables.borrow_mut();
+        tables.push(table);
+        tables.len() - 1
+    }
+
+    // Finds the innermost handler guarding the instruction at `ip`
+    pub fn find_handler(&self, ip: usize) -> Option<ExceptionHandler> {
+        self.handlers
+            .borrow()
+            .iter()
+            .filter(|h| h.start <= ip && ip < h.end)
+            .min_by_key(|h| h.end - h.start)
+            .copied()
+    }
 }
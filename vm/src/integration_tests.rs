@@ -0,0 +1,179 @@
+use crate::chunk::{Chunk, ExceptionHandler, JumpTable};
+use crate::literal::Literal;
+use crate::opcode::OpCode;
+use crate::scanner::Scanner;
+use crate::token::TokenType;
+use crate::value::Value;
+use crate::vm::{InterpreterError, RuntimeError, Vm};
+
+#[test]
+fn test_scanner_block_comments() {
+    let mut scanner = Scanner::new("/* outer /* inner */\n still outer */ and // trailing\n");
+    let token = scanner.scan_token();
+    assert!(matches!(token.token_type, TokenType::And));
+    assert_eq!(token.line, 2);
+    assert!(matches!(scanner.scan_token().token_type, TokenType::Eof));
+}
+
+#[test]
+fn test_scanner_unterminated_block_comment() {
+    let mut scanner = Scanner::new("/* /* */ never closed");
+    let token = scanner.scan_token();
+    assert!(matches!(token.token_type, TokenType::Error));
+    assert_eq!(token.lexeme.as_deref(), Some("Unterminated block comment"));
+}
+
+#[test]
+fn test_scanner_string_escapes() {
+    let mut scanner = Scanner::new(r#""line\nbreak \u{e9}" "\q""#);
+    let token = scanner.scan_token();
+    assert!(matches!(token.literal, Some(Literal::String(ref s)) if s == "line\nbreak \u{e9}"));
+    assert!(matches!(scanner.scan_token().token_type, TokenType::Error));
+}
+
+#[test]
+fn test_scanner_number_literals() {
+    let mut scanner = Scanner::new("0xFF 1_000 1e3 1. 0b12");
+    for expected in &[
+        "Integer(255)",
+        "Integer(1000)",
+        "Number(1000.0)",
+        "Integer(1)",
+    ] {
+        let token = scanner.scan_token();
+        assert_eq!(&format!("{:?}", token.literal.unwrap()), expected);
+    }
+    assert!(matches!(scanner.scan_token().token_type, TokenType::Dot));
+    assert!(matches!(scanner.scan_token().token_type, TokenType::Error));
+}
+
+#[test]
+fn test_value_integer_promotion() {
+    let sum = Value::Integer(1).add(Value::Integer(2)).unwrap();
+    assert!(matches!(sum, Value::Integer(3)));
+    let mixed = Value::Integer(1).add(Value::Number(0.5)).unwrap();
+    assert!(matches!(mixed, Value::Number(n) if n == 1.5));
+    let quotient = Value::Integer(7).int_divide(Value::Integer(2)).unwrap();
+    assert!(matches!(quotient, Value::Integer(3)));
+    assert!(matches!(
+        Value::Integer(i64::MAX).add(Value::Integer(1)),
+        Err(RuntimeError::IntegerOverflow)
+    ));
+    assert!(matches!(
+        Value::Integer(1).modulo(Value::Integer(0)),
+        Err(RuntimeError::DivisionByZero)
+    ));
+}
+
+#[test]
+fn test_vm_exception_table() {
+    let mut chunk = Chunk::new();
+    chunk.write_chunk(OpCode::ConstantInteger(1), 1);
+    chunk.write_chunk(OpCode::ConstantInteger(0), 1);
+    chunk.write_chunk(OpCode::IntDivide, 1);
+    chunk.write_chunk(OpCode::Return, 1);
+    // Handler
+    chunk.write_chunk(OpCode::ConstantInteger(7), 2);
+    chunk.write_chunk(OpCode::Throw, 2);
+    chunk.write_chunk(OpCode::Return, 3);
+
+    // Unhandled errors still abort
+    let mut vm = Vm::new(chunk.clone());
+    assert!(matches!(
+        vm.run(),
+        Err(InterpreterError::Runtime(RuntimeError::DivisionByZero))
+    ));
+
+    chunk.add_handler(ExceptionHandler {
+        start: 0,
+        end: 4,
+        target: 4,
+        stack_depth: 0,
+    });
+    chunk.add_handler(ExceptionHandler {
+        start: 4,
+        end: 6,
+        target: 6,
+        stack_depth: 0,
+    });
+    assert_eq!(chunk.find_handler(2).map(|h| h.target), Some(4));
+    assert_eq!(chunk.find_handler(5).map(|h| h.target), Some(6));
+    assert!(chunk.find_handler(6).is_none());
+
+    let mut vm = Vm::new(chunk);
+    assert!(vm.run().is_ok());
+}
+
+#[test]
+fn test_vm_jumps() {
+    let thrown = |chunk: Chunk| match Vm::new(chunk).run() {
+        Err(InterpreterError::Runtime(RuntimeError::Thrown(value))) => match *value {
+            Value::Integer(i) => i,
+            value => panic!("Unexpected value {:?}", value),
+        },
+        _ => panic!("Expected a thrown value"),
+    };
+
+    // Jump skips the first throw
+    let mut chunk = Chunk::new();
+    chunk.write_chunk(OpCode::Jump(2), 1);
+    chunk.write_chunk(OpCode::ConstantInteger(1), 1);
+    chunk.write_chunk(OpCode::Throw, 1);
+    chunk.write_chunk(OpCode::ConstantInteger(2), 2);
+    chunk.write_chunk(OpCode::Throw, 2);
+    assert_eq!(thrown(chunk), 2);
+
+    // Only nil is falsey, the condition stays on the stack
+    let mut chunk = Chunk::new();
+    chunk.write_chunk(OpCode::ConstantInteger(0), 1);
+    chunk.write_chunk(OpCode::JumpIfFalse(1), 1);
+    chunk.write_chunk(OpCode::Throw, 1);
+    assert_eq!(thrown(chunk), 0);
+
+    // Loop keeps decrementing until the subtraction overflows
+    let mut chunk = Chunk::new();
+    chunk.write_chunk(OpCode::ConstantInteger(i64::MIN + 2), 1);
+    chunk.write_chunk(OpCode::ConstantInteger(1), 2);
+    chunk.write_chunk(OpCode::Subtract, 2);
+    chunk.write_chunk(OpCode::Loop(3), 2);
+    assert!(matches!(
+        Vm::new(chunk).run(),
+        Err(InterpreterError::Runtime(RuntimeError::IntegerOverflow))
+    ));
+
+    // Looping back past the start of the chunk is an error instead of a panic
+    let mut chunk = Chunk::new();
+    chunk.write_chunk(OpCode::Loop(5), 1);
+    assert!(matches!(
+        Vm::new(chunk).run(),
+        Err(InterpreterError::Runtime(RuntimeError::InvalidJump(5)))
+    ));
+}
+
+#[test]
+fn test_vm_jump_table() {
+    let run = |subject: i64| {
+        let mut chunk = Chunk::new();
+        let table = chunk.add_jump_table(JumpTable {
+            cases: [(1, 0), (2, 2)].iter().copied().collect(),
+            default: 4,
+        });
+        chunk.write_chunk(OpCode::ConstantInteger(subject), 1);
+        chunk.write_chunk(OpCode::JumpTable(table), 1);
+        // Each branch throws its value so the taken branch is visible
+        for value in [10, 20, 30].iter() {
+            chunk.write_chunk(OpCode::ConstantInteger(*value), 2);
+            chunk.write_chunk(OpCode::Throw, 2);
+        }
+        match Vm::new(chunk).run() {
+            Err(InterpreterError::Runtime(RuntimeError::Thrown(value))) => match *value {
+                Value::Integer(i) => i,
+                value => panic!("Unexpected value {:?}", value),
+            },
+            _ => panic!("Expected a thrown value"),
+        }
+    };
+    assert_eq!(run(1), 10);
+    assert_eq!(run(2), 20);
+    assert_eq!(run(7), 30);
+}
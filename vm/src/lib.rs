@@ -8,3 +8,6 @@ pub mod scanner;
 pub mod token;
 pub mod value;
 pub mod vm;
+
+#[cfg(test)]
+mod integration_tests;
@@ -1,6 +1,18 @@
+use utils::number::Number;
+
 #[derive(Debug)]
 pub enum Literal {
     String(String),
     Number(f64),
+    Integer(i64),
     Nil,
 }
+
+impl From<Number> for Literal {
+    fn from(number: Number) -> Self {
+        match number {
+            Number::Integer(i) => Literal::Integer(i),
+            Number::Float(n) => Literal::Number(n),
+        }
+    }
+}
@@ -2,6 +2,7 @@
 pub enum OpCode {
     // Values
     ConstantNumber(f64),
+    ConstantInteger(i64),
 
     // Unary Operators
     Negate,
@@ -11,6 +12,26 @@ pub enum OpCode {
     Subtract,
     Multiply,
     Divide,
+    IntDivide,
+    Modulo,
+
+    // Bitwise Operators
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
+
+    // Control flow, offsets are relative to the instruction after the jump
+    Jump(usize),
+    JumpIfFalse(usize),
+    Loop(usize),
+    // Pops the subject of a match and jumps through the chunk's jump table at this index
+    JumpTable(usize),
+    Pop,
+
+    // Exceptions, handlers are registered in the chunk's exception table
+    Throw,
 
     Return,
 }
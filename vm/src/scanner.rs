@@ -1,4 +1,5 @@
-use std::{iter::Peekable, str::Chars, str::FromStr};
+use std::{iter::Peekable, str::Chars};
+use utils::number::parse_number;
 
 use crate::{
     literal::Literal,
@@ -19,23 +20,57 @@ impl<'a> Scanner<'a> {
     }
 
     pub fn scan_token(&mut self) -> Token {
-        self.skip_whitespace();
+        if let Err(error) = self.skip_whitespace() {
+            return error;
+        }
         if let Some(c) = self.source.next() {
-            return match c {
+            match c {
                 '(' => self.make_token(TokenType::LeftParen, Some("("), None),
                 ')' => self.make_token(TokenType::RightParen, Some(")"), None),
                 '{' => self.make_token(TokenType::LeftBrace, Some("{"), None),
                 '}' => self.make_token(TokenType::RightBrace, Some("}"), None),
                 ';' => self.make_token(TokenType::Semicolon, Some(";"), None),
+                ':' => self.make_token(TokenType::Colon, Some(":"), None),
+                '?' => self.make_token(TokenType::Question, Some("?"), None),
                 ',' => self.make_token(TokenType::Comma, Some(","), None),
                 '.' => self.make_token(TokenType::Dot, Some("."), None),
-                '-' => self.make_token(TokenType::Minus, Some("-"), None),
-                '+' => self.make_token(TokenType::Plus, Some("+"), None),
-                '/' => self.make_token(TokenType::Slash, Some("/"), None),
-                '*' => self.make_token(TokenType::Star, Some("*"), None),
+                '-' if self.source.peek() == Some(&'-') => {
+                    self.source.next();
+                    self.make_token(TokenType::MinusMinus, Some("--"), None)
+                }
+                '+' if self.source.peek() == Some(&'+') => {
+                    self.source.next();
+                    self.make_token(TokenType::PlusPlus, Some("++"), None)
+                }
+                '-' => self.match_binary(TokenType::Minus, TokenType::MinusEqual, '-'),
+                '+' => self.match_binary(TokenType::Plus, TokenType::PlusEqual, '+'),
+                '/' => self.match_binary(TokenType::Slash, TokenType::SlashEqual, '/'),
+                '*' => self.match_binary(TokenType::Star, TokenType::StarEqual, '*'),
+                '%' => self.match_binary(TokenType::Percent, TokenType::PercentEqual, '%'),
+                '&' => self.make_token(TokenType::Ampersand, Some("&"), None),
+                '|' => self.make_token(TokenType::Pipe, Some("|"), None),
+                '^' => self.make_token(TokenType::Caret, Some("^"), None),
+
+                // Integer division, '//' is already taken by line comments
+                '~' if self.source.peek() == Some(&'/') => {
+                    self.source.next();
+                    self.make_token(TokenType::TildeSlash, Some("~/"), None)
+                }
+                '<' if self.source.peek() == Some(&'<') => {
+                    self.source.next();
+                    self.make_token(TokenType::LessLess, Some("<<"), None)
+                }
+                '>' if self.source.peek() == Some(&'>') => {
+                    self.source.next();
+                    self.make_token(TokenType::GreaterGreater, Some(">>"), None)
+                }
 
                 // Peek ahead 1 char
                 '!' => self.match_binary(TokenType::Bang, TokenType::BangEqual, '!'),
+                '=' if self.source.peek() == Some(&'>') => {
+                    self.source.next();
+                    self.make_token(TokenType::FatArrow, Some("=>"), None)
+                }
                 '=' => self.match_binary(TokenType::Equal, TokenType::EqualEqual, '='),
                 '<' => self.match_binary(TokenType::Less, TokenType::LessEqual, '<'),
                 '>' => self.match_binary(TokenType::Greater, TokenType::GreaterEqual, '>'),
@@ -43,11 +78,10 @@ impl<'a> Scanner<'a> {
                 '"' => self.match_string(),
                 '0'..='9' => self.match_digit(c),
                 'a'..='z' | 'A'..='Z' | '_' => self.match_identifier(c),
-                '\n' => self.make_token(TokenType::Eof, None, None),
-                _ => panic!("Invalid token: {}, line {}", c, self.line),
-            };
+                _ => self.error_token(format!("Unexpected character {}", c)),
+            }
         } else {
-            panic!("Invalid token, line {}", self.line)
+            self.make_token(TokenType::Eof, None, None)
         }
     }
 
@@ -77,38 +111,100 @@ impl<'a> Scanner<'a> {
                     Some(captured_string.as_str()),
                     Some(Literal::String(captured_string.to_owned())),
                 );
+            } else if c == '\\' {
+                self.source.next();
+                match self.match_escape() {
+                    Ok(escaped) => captured_string.push(escaped),
+                    Err(message) => return self.error_token(message),
+                }
+                continue;
             } else if c == '\n' {
                 self.line += 1;
             }
             captured_string.push(self.source.next().unwrap());
         }
-        panic!("Unterminated string: line {}", start_line);
+        Token::new(
+            TokenType::Error,
+            Some("Unterminated string".to_string()),
+            None,
+            start_line,
+        )
+    }
+
+    fn match_escape(&mut self) -> Result<char, String> {
+        let escaped = match self.source.next() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('$') => '$',
+            Some('u') => return self.match_unicode_escape(),
+            Some(c) => return Err(format!("Invalid escape sequence \\{}", c)),
+            None => return Err("Invalid escape sequence \\".to_string()),
+        };
+        Ok(escaped)
+    }
+
+    // Unicode escapes take the form \u{1F600}
+    fn match_unicode_escape(&mut self) -> Result<char, String> {
+        let mut hex = String::new();
+        if let Some('{') = self.source.peek() {
+            self.source.next();
+            while let Some(&c) = self.source.peek() {
+                if !c.is_ascii_hexdigit() {
+                    break;
+                }
+                hex.push(c);
+                self.source.next();
+            }
+            if let Some('}') = self.source.peek() {
+                self.source.next();
+                let code_point = u32::from_str_radix(hex.as_str(), 16).ok();
+                if let Some(c) = code_point
+                    .filter(|_| hex.len() <= 6)
+                    .and_then(char::from_u32)
+                {
+                    return Ok(c);
+                }
+            }
+        }
+        Err(format!("Invalid unicode escape sequence \\u{{{}}}", hex))
     }
 
+    // Greedily captures everything that could belong to the literal so malformed
+    // input such as 0xFG or 1e is reported as one bad number rather than split tokens
     fn match_digit(&mut self, captured_digit: char) -> Token {
+        let is_decimal =
+            captured_digit != '0' || !matches!(self.source.peek(), Some('x' | 'X' | 'b' | 'B'));
         let mut captured_digit = captured_digit.to_string();
-        while self.is_digit() {
-            let c = self.source.next().unwrap();
-            captured_digit.push(c);
-        }
 
-        if let Some(&'.') = self.source.peek() {
-            captured_digit.push(self.source.next().unwrap());
-            if self.is_digit() {
-                while self.is_digit() {
-                    let c = self.source.next().unwrap();
-                    captured_digit.push(c);
-                }
-            } else {
-                panic!("Expected number after decimal point: line {}", self.line);
+        while let Some(&c) = self.source.peek() {
+            let is_fraction = c == '.'
+                && is_decimal
+                && !captured_digit.contains(['.', 'e', 'E'])
+                && self.peek_next().is_some_and(|n| n.is_ascii_digit());
+            let is_exponent_sign =
+                (c == '+' || c == '-') && is_decimal && captured_digit.ends_with(['e', 'E']);
+            if !(c.is_alphanumeric() || c == '_' || is_fraction || is_exponent_sign) {
+                break;
             }
+            captured_digit.push(c);
+            self.source.next();
         }
 
-        self.make_token(
-            TokenType::Number,
-            Some(captured_digit.as_str()),
-            Some(Literal::Number(f64::from_str(&captured_digit).unwrap())),
-        )
+        match parse_number(captured_digit.as_str()) {
+            Ok(number) => self.make_token(
+                TokenType::Number,
+                Some(captured_digit.as_str()),
+                Some(number.into()),
+            ),
+            Err(reason) => self.error_token(format!(
+                "Malformed number literal '{}': {}",
+                captured_digit, reason
+            )),
+        }
     }
 
     fn match_identifier(&mut self, captured_name: char) -> Token {
@@ -124,13 +220,55 @@ impl<'a> Scanner<'a> {
 
         // check if its a keyword
         match identifier.chars().next().unwrap() {
-            'a' => self.check_keyword("and", TokenType::And, identifier),
-            'c' => self.check_keyword("class", TokenType::Class, identifier),
-            'e' => self.check_keyword("else", TokenType::Else, identifier),
+            'a' => {
+                if let Some(c) = identifier.chars().nth(1) {
+                    match c {
+                        'n' => return self.check_keyword("and", TokenType::And, identifier),
+                        's' => return self.check_keyword("as", TokenType::As, identifier),
+                        _ => {}
+                    };
+                }
+                self.make_token(TokenType::Identifier, Some(identifier.as_str()), None)
+            }
+            'b' => self.check_keyword("break", TokenType::Break, identifier),
+            'c' => {
+                if let Some(c) = identifier.chars().nth(1) {
+                    match c {
+                        'a' => {
+                            return match identifier.chars().nth(2) {
+                                Some('s') => {
+                                    self.check_keyword("case", TokenType::Case, identifier)
+                                }
+                                _ => self.check_keyword("catch", TokenType::Catch, identifier),
+                            }
+                        }
+                        'l' => return self.check_keyword("class", TokenType::Class, identifier),
+                        'o' => {
+                            return self.check_keyword("continue", TokenType::Continue, identifier)
+                        }
+                        _ => {}
+                    }
+                }
+                self.make_token(TokenType::Identifier, Some(identifier.as_str()), None)
+            }
+            'd' => self.check_keyword("default", TokenType::Default, identifier),
+            'e' => {
+                if let Some(c) = identifier.chars().nth(1) {
+                    match c {
+                        'l' => return self.check_keyword("else", TokenType::Else, identifier),
+                        'x' => return self.check_keyword("export", TokenType::Export, identifier),
+                        _ => {}
+                    };
+                }
+                self.make_token(TokenType::Identifier, Some(identifier.as_str()), None)
+            }
             'f' => {
                 if let Some(c) = identifier.chars().nth(1) {
                     match c {
                         'a' => return self.check_keyword("false", TokenType::False, identifier),
+                        'i' => {
+                            return self.check_keyword("finally", TokenType::Finally, identifier)
+                        }
                         'o' => return self.check_keyword("for", TokenType::For, identifier),
                         'u' => return self.check_keyword("fun", TokenType::Fun, identifier),
                         _ => {}
@@ -138,7 +276,18 @@ impl<'a> Scanner<'a> {
                 }
                 self.make_token(TokenType::Identifier, Some(identifier.as_str()), None)
             }
-            'i' => self.check_keyword("if", TokenType::If, identifier),
+            'i' => {
+                if let Some(c) = identifier.chars().nth(1) {
+                    match c {
+                        'f' => return self.check_keyword("if", TokenType::If, identifier),
+                        'm' => return self.check_keyword("import", TokenType::Import, identifier),
+                        'n' => return self.check_keyword("in", TokenType::In, identifier),
+                        _ => {}
+                    };
+                }
+                self.make_token(TokenType::Identifier, Some(identifier.as_str()), None)
+            }
+            'm' => self.check_keyword("match", TokenType::Match, identifier),
             'n' => self.check_keyword("nil", TokenType::Nil, identifier),
             'o' => self.check_keyword("or", TokenType::Or, identifier),
             'p' => self.check_keyword("print", TokenType::Print, identifier),
@@ -147,8 +296,20 @@ impl<'a> Scanner<'a> {
             't' => {
                 if let Some(c) = identifier.chars().nth(1) {
                     match c {
-                        'h' => return self.check_keyword("this", TokenType::This, identifier),
-                        'r' => return self.check_keyword("true", TokenType::True, identifier),
+                        'h' => {
+                            return match identifier.chars().nth(2) {
+                                Some('r') => {
+                                    self.check_keyword("throw", TokenType::Throw, identifier)
+                                }
+                                _ => self.check_keyword("this", TokenType::This, identifier),
+                            }
+                        }
+                        'r' => {
+                            return match identifier.chars().nth(2) {
+                                Some('y') => self.check_keyword("try", TokenType::Try, identifier),
+                                _ => self.check_keyword("true", TokenType::True, identifier),
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -178,36 +339,83 @@ impl<'a> Scanner<'a> {
 
     fn is_digit(&mut self) -> bool {
         if let Some(c) = self.source.peek() {
-            return ('0'..='9').contains(c);
+            return c.is_ascii_digit();
         }
         false
     }
 
-    fn skip_whitespace(&mut self) {
+    fn peek_next(&self) -> Option<char> {
+        let mut lookahead = self.source.clone();
+        lookahead.next();
+        lookahead.next()
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), Token> {
         while let Some(&c) = self.source.peek() {
-            if !c.is_whitespace() {
-                return;
-            } else if c == '\n' {
-                self.line += 1;
-                break;
-            } else if c == '/' {
-                self.skip_comments();
+            match c {
+                '\n' => {
+                    self.line += 1;
+                    self.source.next();
+                }
+                '/' => match self.peek_next() {
+                    Some('/') => self.skip_line_comment(),
+                    Some('*') => self.skip_block_comment()?,
+                    _ => return Ok(()),
+                },
+                c if c.is_whitespace() => {
+                    self.source.next();
+                }
+                _ => return Ok(()),
+            }
+        }
+        Ok(())
+    }
+
+    fn skip_line_comment(&mut self) {
+        while let Some(&c) = self.source.peek() {
+            if c == '\n' {
                 break;
             }
             self.source.next();
         }
     }
 
-    fn skip_comments(&mut self) {
-        if let Some(&'/') = self.source.peek() {
-            while let Some(&c) = self.source.peek() {
-                self.source.next();
-                if c == '\n' {
-                    self.line += 1;
-                    break;
+    // Block comments can be nested, so track how many are still open
+    fn skip_block_comment(&mut self) -> Result<(), Token> {
+        let start_line = self.line;
+        // Consume the opening '/*'
+        self.source.next();
+        self.source.next();
+
+        let mut depth = 1;
+        while let Some(c) = self.source.next() {
+            match c {
+                '\n' => self.line += 1,
+                '/' if self.source.peek() == Some(&'*') => {
+                    self.source.next();
+                    depth += 1;
                 }
+                '*' if self.source.peek() == Some(&'/') => {
+                    self.source.next();
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                _ => {}
             }
         }
+
+        Err(Token::new(
+            TokenType::Error,
+            Some("Unterminated block comment".to_string()),
+            None,
+            start_line,
+        ))
+    }
+
+    fn error_token(&self, message: String) -> Token {
+        Token::new(TokenType::Error, Some(message), None, self.line)
     }
 
     fn make_token(
@@ -12,8 +12,14 @@ pub enum TokenType {
     Minus,
     Plus,
     Semicolon,
+    Colon,
+    Question,
     Slash,
     Star,
+    Percent,
+    Ampersand,
+    Pipe,
+    Caret,
 
     // Single or double char tokens
     Bang,
@@ -24,30 +30,58 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    LessLess,
+    GreaterGreater,
+    TildeSlash,
+    // `=>` between match patterns and their body
+    FatArrow,
 
     // Literals
     Identifier,
     String,
     Number,
 
+    // Compound assignment and increment operators
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    PercentEqual,
+    PlusPlus,
+    MinusMinus,
+
     // Keywords
     And,
+    As,
+    Break,
+    Case,
+    Catch,
     Class,
+    Continue,
+    Default,
     Else,
+    Export,
     False,
+    Finally,
     For,
     Fun,
     If,
+    Import,
+    In,
+    Match,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
 
+    Error,
     Eof,
 }
 
@@ -1,35 +1,117 @@
+use std::convert::TryFrom;
+
+use crate::vm::RuntimeError;
+
+type ValueResult = Result<Value, RuntimeError>;
+
 #[derive(Debug)]
 pub enum Value {
     Number(f64),
+    Integer(i64),
     Nil,
+    // A runtime error caught by an exception handler
+    Error(Box<RuntimeError>),
 }
 
 impl Value {
-    pub fn add(&self, other: Value) -> Value {
+    pub fn is_falsey(&self) -> bool {
+        matches!(self, Value::Nil)
+    }
+
+    pub fn add(&self, other: Value) -> ValueResult {
+        self.arithmetic(other, "+", i64::checked_add, |a, b| a + b)
+    }
+
+    pub fn subtract(&self, other: Value) -> ValueResult {
+        self.arithmetic(other, "-", i64::checked_sub, |a, b| a - b)
+    }
+
+    pub fn multiply(&self, other: Value) -> ValueResult {
+        self.arithmetic(other, "*", i64::checked_mul, |a, b| a * b)
+    }
+
+    // Division always produces a float, use int_divide for integer division
+    pub fn divide(&self, other: Value) -> ValueResult {
         match (self, other) {
-            (Value::Number(a), Value::Number(b)) => Value::Number(a + b),
-            _ => panic!("Invalid use of '+'"),
+            (Value::Integer(a), Value::Integer(b)) => Ok(Value::Number(*a as f64 / b as f64)),
+            (a, b) => a.arithmetic(b, "/", |_, _| None, |a, b| a / b),
         }
     }
 
-    pub fn subtract(&self, other: Value) -> Value {
-        match (self, other) {
-            (Value::Number(a), Value::Number(b)) => Value::Number(a - b),
-            _ => panic!("Invalid use of '+'"),
+    pub fn int_divide(&self, other: Value) -> ValueResult {
+        if let (Value::Integer(_), Value::Integer(0)) = (self, &other) {
+            return Err(RuntimeError::DivisionByZero);
         }
+        self.arithmetic(other, "~/", i64::checked_div, |a, b| (a / b).trunc())
+    }
+
+    pub fn modulo(&self, other: Value) -> ValueResult {
+        if let (Value::Integer(_), Value::Integer(0)) = (self, &other) {
+            return Err(RuntimeError::DivisionByZero);
+        }
+        self.arithmetic(other, "%", i64::checked_rem, |a, b| a % b)
+    }
+
+    pub fn bit_and(&self, other: Value) -> ValueResult {
+        self.bitwise(other, "&", |a, b| Some(a & b))
+    }
+
+    pub fn bit_or(&self, other: Value) -> ValueResult {
+        self.bitwise(other, "|", |a, b| Some(a | b))
+    }
+
+    pub fn bit_xor(&self, other: Value) -> ValueResult {
+        self.bitwise(other, "^", |a, b| Some(a ^ b))
+    }
+
+    pub fn shift_left(&self, other: Value) -> ValueResult {
+        self.bitwise(other, "<<", |a, b| {
+            u32::try_from(b).ok().and_then(|b| a.checked_shl(b))
+        })
+    }
+
+    pub fn shift_right(&self, other: Value) -> ValueResult {
+        self.bitwise(other, ">>", |a, b| {
+            u32::try_from(b).ok().and_then(|b| a.checked_shr(b))
+        })
     }
 
-    pub fn multiply(&self, other: Value) -> Value {
+    // Integers stay integers and fail on overflow, mixing in a float promotes both sides
+    fn arithmetic(
+        &self,
+        other: Value,
+        op: &str,
+        integer_op: fn(i64, i64) -> Option<i64>,
+        float_op: fn(f64, f64) -> f64,
+    ) -> ValueResult {
         match (self, other) {
-            (Value::Number(a), Value::Number(b)) => Value::Number(a * b),
-            _ => panic!("Invalid use of '+'"),
+            (Value::Integer(a), Value::Integer(b)) => integer_op(*a, b)
+                .map(Value::Integer)
+                .ok_or(RuntimeError::IntegerOverflow),
+            (Value::Number(a), Value::Number(b)) => Ok(Value::Number(float_op(*a, b))),
+            (Value::Integer(a), Value::Number(b)) => Ok(Value::Number(float_op(*a as f64, b))),
+            (Value::Number(a), Value::Integer(b)) => Ok(Value::Number(float_op(*a, b as f64))),
+            _ => Err(RuntimeError::InvalidOperand(format!(
+                "Invalid use of '{}'",
+                op
+            ))),
         }
     }
 
-    pub fn divide(&self, other: Value) -> Value {
+    fn bitwise(
+        &self,
+        other: Value,
+        op: &str,
+        integer_op: fn(i64, i64) -> Option<i64>,
+    ) -> ValueResult {
         match (self, other) {
-            (Value::Number(a), Value::Number(b)) => Value::Number(a / b),
-            _ => panic!("Invalid use of '+'"),
+            (Value::Integer(a), Value::Integer(b)) => integer_op(*a, b)
+                .map(Value::Integer)
+                .ok_or(RuntimeError::IntegerOverflow),
+            _ => Err(RuntimeError::InvalidOperand(format!(
+                "Invalid use of '{}'",
+                op
+            ))),
         }
     }
 }
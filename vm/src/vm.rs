@@ -5,10 +5,19 @@ use crate::{chunk::Chunk, disassembler, opcode::OpCode, value::Value};
 #[derive(Debug)]
 pub enum CompileError {
     InvalidOperand(String),
+    InvalidToken(String, usize),
 }
 
 #[derive(Debug)]
-pub enum RuntimeError {}
+pub enum RuntimeError {
+    InvalidOperand(String),
+    IntegerOverflow,
+    DivisionByZero,
+    // A jump whose target lies before the start of the chunk
+    InvalidJump(usize),
+    // Raised by the Throw instruction
+    Thrown(Box<Value>),
+}
 
 #[derive(Debug)]
 pub enum InterpreterError {
@@ -36,47 +45,127 @@ impl Vm {
         loop {
             let tok = scanner.scan_token();
             println!("{:?}", tok);
-            if let TokenType::Eof = tok.token_type {
-                break;
+            match tok.token_type {
+                TokenType::Eof => break,
+                TokenType::Error => {
+                    return Err(InterpreterError::Compile(CompileError::InvalidToken(
+                        tok.lexeme.unwrap_or_default(),
+                        tok.line,
+                    )))
+                }
+                _ => {}
             }
         }
         Ok(())
     }
 
-    fn run(&mut self) -> InterpreterResult<()> {
-        for (idx, op_code_line) in self.chunk.clone().code.borrow_mut().iter().enumerate() {
+    pub(crate) fn run(&mut self) -> InterpreterResult<()> {
+        let chunk = self.chunk.clone();
+        let code = chunk.code.borrow();
+        let mut ip = 0;
+        while let Some(op_code_line) = code.get(ip) {
             // Debug utils
-            disassembler::disassemble_instruction(op_code_line, idx);
+            disassembler::disassemble_instruction(op_code_line, ip);
             self.print_stack();
 
-            match op_code_line.code {
-                OpCode::ConstantNumber(val) => self.stack.push(Value::Number(val)),
-                OpCode::Negate => {
-                    let val = self.pop();
-                    if let Value::Number(n) = val {
-                        self.push(Value::Number(-n));
-                    } else {
+            ip += 1;
+            match self.execute(&op_code_line.code, &mut ip) {
+                Ok(true) => return Ok(()),
+                Ok(false) => {}
+                // Unwind to the innermost handler guarding the failed instruction
+                Err(InterpreterError::Runtime(err)) => match chunk.find_handler(ip - 1) {
+                    Some(handler) => {
+                        self.stack.truncate(handler.stack_depth);
+                        self.push(match err {
+                            RuntimeError::Thrown(value) => *value,
+                            err => Value::Error(Box::new(err)),
+                        });
+                        ip = handler.target;
+                    }
+                    None => return Err(InterpreterError::Runtime(err)),
+                },
+                Err(err) => return Err(err),
+            }
+        }
+        self.print_stack();
+        Ok(())
+    }
+
+    // Executes a single instruction, returns true once the chunk has returned
+    fn execute(&mut self, op: &OpCode, ip: &mut usize) -> InterpreterResult<bool> {
+        match op {
+            OpCode::ConstantNumber(val) => self.stack.push(Value::Number(*val)),
+            OpCode::ConstantInteger(val) => self.stack.push(Value::Integer(*val)),
+            OpCode::Negate => {
+                let val = self.pop();
+                match val {
+                    Value::Number(n) => self.push(Value::Number(-n)),
+                    Value::Integer(i) => match i.checked_neg() {
+                        Some(i) => self.push(Value::Integer(i)),
+                        None => {
+                            return Err(InterpreterError::Runtime(RuntimeError::IntegerOverflow))
+                        }
+                    },
+                    _ => {
                         return Err(InterpreterError::Compile(CompileError::InvalidOperand(
                             format!("Invalid right hand side of '-': {:?}", val),
-                        )));
+                        )))
                     }
                 }
-                OpCode::Add | OpCode::Subtract | OpCode::Multiply | OpCode::Divide => {
-                    let res = self.binary_op(&op_code_line.code);
-                    self.push(res);
-                }
-                OpCode::Return => {
-                    let val = self.stack.pop().unwrap();
-                    println!("{:?}", val);
-                    return Ok(());
+            }
+            OpCode::Add
+            | OpCode::Subtract
+            | OpCode::Multiply
+            | OpCode::Divide
+            | OpCode::IntDivide
+            | OpCode::Modulo
+            | OpCode::BitAnd
+            | OpCode::BitOr
+            | OpCode::BitXor
+            | OpCode::ShiftLeft
+            | OpCode::ShiftRight => {
+                let res = self.binary_op(op).map_err(InterpreterError::Runtime)?;
+                self.push(res);
+            }
+            OpCode::Pop => {
+                self.pop();
+            }
+            // Loop exits, `break` and `continue` all compile down to these jumps
+            OpCode::Jump(offset) => *ip += offset,
+            OpCode::JumpIfFalse(offset) => {
+                if self.peek().is_falsey() {
+                    *ip += offset;
                 }
             }
+            OpCode::Loop(offset) => {
+                *ip = ip.checked_sub(*offset).ok_or(InterpreterError::Runtime(
+                    RuntimeError::InvalidJump(*offset),
+                ))?;
+            }
+            OpCode::JumpTable(index) => {
+                let subject = self.pop();
+                let tables = self.chunk.jump_tables.borrow();
+                *ip += match subject {
+                    Value::Integer(subject) => tables[*index].offset(subject),
+                    _ => tables[*index].default,
+                };
+            }
+            OpCode::Throw => {
+                let val = self.pop();
+                return Err(InterpreterError::Runtime(RuntimeError::Thrown(Box::new(
+                    val,
+                ))));
+            }
+            OpCode::Return => {
+                let val = self.stack.pop().unwrap();
+                println!("{:?}", val);
+                return Ok(true);
+            }
         }
-        self.print_stack();
-        Ok(())
+        Ok(false)
     }
 
-    fn binary_op(&mut self, op: &OpCode) -> Value {
+    fn binary_op(&mut self, op: &OpCode) -> Result<Value, RuntimeError> {
         let b = self.pop();
         let a = self.pop();
 
@@ -85,6 +174,13 @@ impl Vm {
             OpCode::Subtract => a.subtract(b),
             OpCode::Multiply => a.multiply(b),
             OpCode::Divide => a.divide(b),
+            OpCode::IntDivide => a.int_divide(b),
+            OpCode::Modulo => a.modulo(b),
+            OpCode::BitAnd => a.bit_and(b),
+            OpCode::BitOr => a.bit_or(b),
+            OpCode::BitXor => a.bit_xor(b),
+            OpCode::ShiftLeft => a.shift_left(b),
+            OpCode::ShiftRight => a.shift_right(b),
             _ => panic!("Opcode cannot be used for binary operations"),
         }
     }
@@ -99,6 +195,12 @@ impl Vm {
         self.stack.push(val);
     }
 
+    fn peek(&self) -> &Value {
+        self.stack
+            .last()
+            .expect("Cannot call peek() on empty stack")
+    }
+
     fn pop(&mut self) -> Value {
         if let Some(val) = self.stack.pop() {
             val